@@ -0,0 +1,113 @@
+use crate::data::{AtlasFrames, AtlasLayout};
+use bevy::{prelude::*, utils::HashMap};
+
+pub struct AtlasPlugin;
+impl Plugin for AtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AtlasLayouts>()
+            .register_type::<UiAtlasAnimation>()
+            .add_systems(Update, update_atlas_animations);
+    }
+}
+
+/// frames per second, if an image sets `atlas_frames` without `fps`
+pub const DEFAULT_ATLAS_FPS: f32 = 12.;
+
+/// one layout asset per distinct `atlas_layout`,
+/// shared by all images using it.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct AtlasLayouts(HashMap<AtlasLayout, Handle<TextureAtlasLayout>>);
+
+impl AtlasLayouts {
+    pub fn get_or_add(
+        &mut self,
+        layout: AtlasLayout,
+        server: &AssetServer,
+    ) -> Handle<TextureAtlasLayout> {
+        self.entry(layout)
+            .or_insert_with(|| server.add(layout.build()))
+            .clone()
+    }
+}
+
+/// plays the atlas frames of an image in a loop,
+/// added by `atlas_frames="0..7"` and `fps="12"`.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect]
+pub struct UiAtlasAnimation {
+    /// all frames of the layout if not set
+    pub frames: Option<AtlasFrames>,
+    pub timer: Timer,
+}
+
+impl Default for UiAtlasAnimation {
+    fn default() -> Self {
+        Self::new(None, DEFAULT_ATLAS_FPS)
+    }
+}
+
+impl UiAtlasAnimation {
+    pub fn new(frames: Option<AtlasFrames>, fps: f32) -> Self {
+        Self {
+            frames,
+            timer: Timer::from_seconds(frame_duration(fps), TimerMode::Repeating),
+        }
+    }
+
+    pub fn set_fps(&mut self, fps: f32) {
+        self.timer
+            .set_duration(std::time::Duration::from_secs_f32(frame_duration(fps)));
+    }
+}
+
+fn frame_duration(fps: f32) -> f32 {
+    1. / fps.max(0.01)
+}
+
+fn update_atlas_animations(
+    time: Res<Time<Real>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut images: Query<(&mut UiAtlasAnimation, &mut ImageNode)>,
+) {
+    for (mut animation, mut image) in images.iter_mut() {
+        animation.timer.tick(time.delta());
+        let steps = animation.timer.times_finished_this_tick() as usize;
+        if steps == 0 {
+            continue;
+        }
+
+        let Some(atlas) = image.texture_atlas.as_mut() else {
+            continue;
+        };
+
+        let frames = match animation.frames {
+            Some(frames) => frames,
+            None => {
+                let Some(layout) = layouts.get(&atlas.layout) else {
+                    continue;
+                };
+
+                AtlasFrames {
+                    first: 0,
+                    last: layout.len().saturating_sub(1),
+                }
+            }
+        };
+
+        atlas.index = frames.advance(atlas.index, steps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0, 1 => 1)]
+    #[test_case(7, 1 => 0)]
+    #[test_case(6, 3 => 1)]
+    #[test_case(12, 1 => 0)]
+    fn test_advance_frames(index: usize, steps: usize) -> usize {
+        AtlasFrames { first: 0, last: 7 }.advance(index, steps)
+    }
+}
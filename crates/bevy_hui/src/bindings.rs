@@ -1,21 +1,47 @@
-use crate::{build::HtmlNode, data::HtmlTemplate};
+use crate::{
+    build::{HtmlNode, TemplateProperties, TemplateScope, UiBind},
+    compile::CompileContextEvent,
+    data::{FnCall, HtmlTemplate},
+    reflect::{is_reflected, write_reflected},
+    styles::UiDisabled,
+};
 use bevy::{
     ecs::system::{EntityCommands, SystemId, SystemParam},
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    picking::{focus::HoverMap, pointer::PointerButton},
     prelude::*,
-    utils::HashMap,
+    utils::{HashMap, HashSet},
 };
+use std::str::FromStr;
+use std::time::Duration;
 
 pub struct BindingPlugin;
 impl Plugin for BindingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<FunctionBindings>()
             .init_resource::<ComponentBindings>()
+            .register_type::<UiDrag>()
+            .register_type::<UiScroll>()
             .add_event::<UiChangedEvent>()
-            .add_systems(Update, (observe_interactions, observe_on_spawn))
-            .add_observer(observe_node_changed);
+            .add_systems(
+                Update,
+                (observe_interactions, observe_on_spawn, observe_scroll),
+            )
+            .add_observer(observe_node_changed)
+            .add_observer(observe_release)
+            .add_observer(observe_click)
+            .add_observer(observe_drag_start)
+            .add_observer(observe_drag)
+            .add_observer(observe_drag_end);
     }
 }
 
+/// max time between two clicks to count as `on_double_click`
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(300);
+
+/// pixel per line for line based mouse wheels
+const SCROLL_LINE_HEIGHT: f32 = 20.;
+
 /// A user triggered event to notify about a change. This
 /// will trigger any attached [crate::build::OnUiChange]
 /// via an entity obverser function binding.
@@ -27,6 +53,23 @@ impl Plugin for BindingPlugin {
 #[derive(Event)]
 pub struct UiChangedEvent;
 
+/// The current drag movement of a node, updated
+/// before any `on_drag_start`, `on_drag` or `on_drag_end` runs.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[reflect]
+pub struct UiDrag {
+    /// change since the last drag event
+    pub delta: Vec2,
+    /// total distance since the drag started
+    pub distance: Vec2,
+}
+
+/// The last mouse wheel delta in pixel, updated
+/// before any `on_scroll` runs.
+#[derive(Component, Debug, Default, Clone, Copy, Deref, Reflect)]
+#[reflect]
+pub struct UiScroll(pub Vec2);
+
 pub type SpawnFunction = dyn Fn(EntityCommands) + Send + Sync + 'static;
 
 #[derive(SystemParam)]
@@ -43,6 +86,109 @@ impl<'w, 's> HtmlFunctions<'w, 's> {
         let id = self.cmd.register_system(func);
         self.bindings.register(name, id);
     }
+
+    /// register a function taking call arguments
+    ///
+    /// in templates: `on_press="buy_item(sword, {amount})"`
+    pub fn register_with_args<S, M>(&mut self, name: impl Into<String>, func: S)
+    where
+        S: IntoSystem<In<(Entity, HuiArgs)>, (), M> + 'static,
+    {
+        let id = self.cmd.register_system(func);
+        self.bindings.register_with_args(name, id);
+    }
+}
+
+/// Arguments of a function binding call.
+///
+/// `on_press="buy_item(sword, 3)"`
+/// `
+/// let item = args.str(0);
+/// let amount = args.get::<u32>(1);
+/// `
+#[derive(Debug, Clone, Default, Deref, Reflect, PartialEq)]
+pub struct HuiArgs(Vec<String>);
+
+impl HuiArgs {
+    pub fn new(args: Vec<String>) -> Self {
+        Self(args)
+    }
+
+    /// raw argument at index
+    pub fn str(&self, index: usize) -> Option<&str> {
+        self.0.get(index).map(|arg| arg.as_str())
+    }
+
+    /// argument at index parsed as `T`, `None` if missing or invalid
+    pub fn get<T: FromStr>(&self, index: usize) -> Option<T> {
+        self.str(index)?.parse().ok()
+    }
+
+    pub fn f32(&self, index: usize) -> Option<f32> {
+        self.get(index)
+    }
+
+    pub fn i32(&self, index: usize) -> Option<i32> {
+        self.get(index)
+    }
+
+    pub fn u32(&self, index: usize) -> Option<u32> {
+        self.get(index)
+    }
+
+    pub fn bool(&self, index: usize) -> Option<bool> {
+        self.get(index)
+    }
+}
+
+/// read and write the property a node is bound to
+/// with `bind="{key}"`. Used to build two way widgets.
+#[derive(SystemParam)]
+pub struct HtmlBindings<'w, 's> {
+    cmd: Commands<'w, 's>,
+    binds: Query<'w, 's, (&'static UiBind, Option<&'static TemplateScope>)>,
+    properties: Query<'w, 's, &'static mut TemplateProperties>,
+}
+
+impl<'w, 's> HtmlBindings<'w, 's> {
+    /// current value of the bound property
+    pub fn read(&self, entity: Entity) -> Option<&str> {
+        let (bind, scope) = self.binds.get(entity).ok()?;
+        let scope = scope.map(|s| **s).unwrap_or(entity);
+        self.properties
+            .get(scope)
+            .ok()?
+            .get(&**bind)
+            .map(|value| value.as_str())
+    }
+
+    /// writes the bound property and recompiles the owning template.
+    /// Reflected properties are written back to their source.
+    pub fn write(&mut self, entity: Entity, value: impl ToString) {
+        let Ok((bind, scope)) = self.binds.get(entity) else {
+            return;
+        };
+
+        let scope = scope.map(|s| **s).unwrap_or(entity);
+        let Ok(mut props) = self.properties.get_mut(scope) else {
+            return;
+        };
+
+        let value = value.to_string();
+        if props.get(&**bind) == Some(&value) {
+            return;
+        }
+
+        props.insert(bind.to_string(), value.clone());
+
+        if is_reflected(bind) {
+            let key = bind.to_string();
+            self.cmd
+                .queue(move |world: &mut World| write_reflected(world, entity, &key, &value));
+        }
+
+        self.cmd.trigger_targets(CompileContextEvent, scope);
+    }
 }
 
 #[derive(SystemParam)]
@@ -117,20 +263,39 @@ impl ComponentBindings {
 /// FunctionBindings.register("start_game", system_id);
 /// `
 #[derive(Resource, Default, Deref, DerefMut, Debug)]
-pub struct FunctionBindings(HashMap<String, SystemId<In<Entity>>>);
+pub struct FunctionBindings(HashMap<String, FunctionBinding>);
+
+#[derive(Debug, Clone, Copy)]
+pub enum FunctionBinding {
+    Entity(SystemId<In<Entity>>),
+    WithArgs(SystemId<In<(Entity, HuiArgs)>>),
+}
 
 impl FunctionBindings {
     pub fn register(&mut self, key: impl Into<String>, system_id: SystemId<In<Entity>>) {
         let key: String = key.into();
-        self.insert(key, system_id);
+        self.insert(key, FunctionBinding::Entity(system_id));
     }
 
-    pub fn maybe_run(&self, key: &String, entity: Entity, cmd: &mut Commands) {
-        self.get(key)
-            .map(|id| {
+    pub fn register_with_args(
+        &mut self,
+        key: impl Into<String>,
+        system_id: SystemId<In<(Entity, HuiArgs)>>,
+    ) {
+        let key: String = key.into();
+        self.insert(key, FunctionBinding::WithArgs(system_id));
+    }
+
+    pub fn maybe_run(&self, call: &FnCall, entity: Entity, cmd: &mut Commands) {
+        match self.get(&call.name) {
+            Some(FunctionBinding::Entity(id)) => {
                 cmd.run_system_with_input(*id, entity);
-            })
-            .unwrap_or_else(|| warn!("function `{key}` is not bound"));
+            }
+            Some(FunctionBinding::WithArgs(id)) => {
+                cmd.run_system_with_input(*id, (entity, HuiArgs::new(call.args.clone())));
+            }
+            None => warn!("function `{}` is not bound", call.name),
+        }
     }
 }
 
@@ -151,13 +316,17 @@ fn observe_on_spawn(
 #[rustfmt::skip]
 fn observe_interactions(
     mut cmd: Commands,
-    interactions: Query<(Entity, &Interaction), Changed<Interaction>>,
+    interactions: Query<(Entity, &Interaction, Has<UiDisabled>), Changed<Interaction>>,
     function_bindings: Res<FunctionBindings>,
     on_pressed : Query<&crate::prelude::OnUiPress>,
     on_enter : Query<&crate::prelude::OnUiEnter>,
     on_exit : Query<&crate::prelude::OnUiExit>,
 ){
-    interactions.iter().for_each(|(entity, interaction)|{
+    interactions.iter().for_each(|(entity, interaction, disabled)|{
+        if disabled {
+            return;
+        }
+
         match interaction {
             Interaction::Pressed => {
                 if let Ok(crate::prelude::OnUiPress(funcs)) = on_pressed.get(entity){
@@ -189,7 +358,7 @@ fn observe_interactions(
 fn observe_node_changed(
     trigger: Trigger<UiChangedEvent>,
     mut cmd: Commands,
-    on_change: Query<&crate::prelude::OnUiChange>,
+    on_change: Query<&crate::prelude::OnUiChange, Without<UiDisabled>>,
     function_bindings: Res<FunctionBindings>,
 ) {
     let entity = trigger.entity();
@@ -202,3 +371,371 @@ fn observe_node_changed(
         function_bindings.maybe_run(fn_str, entity, &mut cmd);
     }
 }
+
+pub(crate) fn run_all(
+    funcs: &[FnCall],
+    entity: Entity,
+    bindings: &FunctionBindings,
+    cmd: &mut Commands,
+) {
+    for call in funcs.iter() {
+        bindings.maybe_run(call, entity, cmd);
+    }
+}
+
+/// disabled nodes swallow any `Pointer` event,
+/// ancestors will not see it either.
+fn is_blocked<E: Event>(trigger: &mut Trigger<E>, disabled: &Query<(), With<UiDisabled>>) -> bool {
+    let blocked = disabled.contains(trigger.entity());
+    if blocked {
+        trigger.propagate(false);
+    }
+    blocked
+}
+
+/// `Pointer` events bubble up the hierarchy,
+/// each node with a listener on the way runs its functions.
+fn observe_release(
+    mut trigger: Trigger<Pointer<Up>>,
+    mut cmd: Commands,
+    on_release: Query<&crate::prelude::OnUiRelease>,
+    disabled: Query<(), With<UiDisabled>>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    if is_blocked(&mut trigger, &disabled) || trigger.button != PointerButton::Primary {
+        return;
+    }
+
+    let entity = trigger.entity();
+    if let Ok(funcs) = on_release.get(entity) {
+        run_all(funcs, entity, &function_bindings, &mut cmd);
+    }
+}
+
+fn observe_click(
+    mut trigger: Trigger<Pointer<Click>>,
+    mut cmd: Commands,
+    time: Res<Time<Real>>,
+    mut last_clicks: Local<HashMap<Entity, Duration>>,
+    listeners: Query<(
+        Option<&crate::prelude::OnUiClick>,
+        Option<&crate::prelude::OnUiDoubleClick>,
+        Option<&crate::prelude::OnUiRightClick>,
+    )>,
+    disabled: Query<(), With<UiDisabled>>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    if is_blocked(&mut trigger, &disabled) {
+        return;
+    }
+
+    let entity = trigger.entity();
+    let Ok((on_click, on_double_click, on_right_click)) = listeners.get(entity) else {
+        return;
+    };
+
+    match trigger.button {
+        PointerButton::Primary => {
+            if let Some(funcs) = on_click {
+                run_all(funcs, entity, &function_bindings, &mut cmd);
+            }
+
+            let Some(funcs) = on_double_click else {
+                return;
+            };
+
+            let now = time.elapsed();
+            last_clicks.retain(|_, last| now - *last <= DOUBLE_CLICK_TIME);
+
+            if last_clicks.remove(&entity).is_some() {
+                run_all(funcs, entity, &function_bindings, &mut cmd);
+            } else {
+                last_clicks.insert(entity, now);
+            }
+        }
+        PointerButton::Secondary => {
+            if let Some(funcs) = on_right_click {
+                run_all(funcs, entity, &function_bindings, &mut cmd);
+            }
+        }
+        PointerButton::Middle => (),
+    }
+}
+
+fn observe_drag_start(
+    mut trigger: Trigger<Pointer<DragStart>>,
+    mut cmd: Commands,
+    on_drag_start: Query<&crate::prelude::OnUiDragStart>,
+    disabled: Query<(), With<UiDisabled>>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    if is_blocked(&mut trigger, &disabled) {
+        return;
+    }
+
+    let entity = trigger.entity();
+    let Ok(funcs) = on_drag_start.get(entity) else {
+        return;
+    };
+
+    cmd.entity(entity).insert(UiDrag::default());
+    run_all(funcs, entity, &function_bindings, &mut cmd);
+}
+
+fn observe_drag(
+    mut trigger: Trigger<Pointer<Drag>>,
+    mut cmd: Commands,
+    on_drag: Query<&crate::prelude::OnUiDrag>,
+    disabled: Query<(), With<UiDisabled>>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    if is_blocked(&mut trigger, &disabled) {
+        return;
+    }
+
+    let entity = trigger.entity();
+    let Ok(funcs) = on_drag.get(entity) else {
+        return;
+    };
+
+    cmd.entity(entity).insert(UiDrag {
+        delta: trigger.delta,
+        distance: trigger.distance,
+    });
+    run_all(funcs, entity, &function_bindings, &mut cmd);
+}
+
+fn observe_drag_end(
+    mut trigger: Trigger<Pointer<DragEnd>>,
+    mut cmd: Commands,
+    on_drag_end: Query<&crate::prelude::OnUiDragEnd>,
+    disabled: Query<(), With<UiDisabled>>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    if is_blocked(&mut trigger, &disabled) {
+        return;
+    }
+
+    let entity = trigger.entity();
+    let Ok(funcs) = on_drag_end.get(entity) else {
+        return;
+    };
+
+    cmd.entity(entity).insert(UiDrag {
+        delta: Vec2::ZERO,
+        distance: trigger.distance,
+    });
+    run_all(funcs, entity, &function_bindings, &mut cmd);
+}
+
+/// runs `on_scroll` of the closest hovered node
+/// with a listener, unless a disabled node is in the way.
+/// Each listener runs once per wheel event.
+fn observe_scroll(
+    mut cmd: Commands,
+    mut wheel: EventReader<MouseWheel>,
+    hover_map: Option<Res<HoverMap>>,
+    parents: Query<&Parent>,
+    on_scroll: Query<&crate::prelude::OnUiScroll>,
+    disabled: Query<(), With<UiDisabled>>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    let Some(hover_map) = hover_map else {
+        wheel.clear();
+        return;
+    };
+
+    for event in wheel.read() {
+        let delta = match event.unit {
+            MouseScrollUnit::Line => Vec2::new(event.x, event.y) * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => Vec2::new(event.x, event.y),
+        };
+
+        let mut scrolled = HashSet::new();
+        for hovered in hover_map.values().flat_map(|hits| hits.keys()) {
+            let Some((entity, funcs)) = std::iter::once(*hovered)
+                .chain(parents.iter_ancestors(*hovered))
+                .take_while(|entity| !disabled.contains(*entity))
+                .find_map(|entity| on_scroll.get(entity).ok().map(|funcs| (entity, funcs)))
+            else {
+                continue;
+            };
+
+            if !scrolled.insert(entity) {
+                continue;
+            }
+
+            cmd.entity(entity).insert(UiScroll(delta));
+            run_all(funcs, entity, &function_bindings, &mut cmd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compile::CompileContentEvent, spawn_template, test_app};
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        picking::{backend::HitData, pointer::PointerId},
+    };
+
+    #[derive(Resource, Default)]
+    struct Scrolls(usize);
+
+    #[derive(Resource, Default)]
+    struct Compiles(usize);
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct Settings {
+        volume: u32,
+    }
+
+    fn bound(app: &mut App) -> Entity {
+        let world = app.world_mut();
+        world.query_filtered::<Entity, With<UiBind>>().single(world)
+    }
+
+    fn text(app: &mut App) -> Vec<String> {
+        app.world_mut()
+            .query::<&Text>()
+            .iter(app.world())
+            .map(|text| text.0.clone())
+            .collect()
+    }
+
+    fn write(app: &mut App, entity: Entity, value: &'static str) {
+        app.world_mut()
+            .run_system_once(move |mut bindings: HtmlBindings| bindings.write(entity, value))
+            .unwrap();
+    }
+
+    fn read(app: &mut App, entity: Entity) -> Option<String> {
+        app.world_mut()
+            .run_system_once(move |bindings: HtmlBindings| {
+                bindings.read(entity).map(|value| value.to_string())
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_write_binding() {
+        let mut app = test_app();
+        let root = spawn_template(
+            &mut app,
+            r#"<template><property name="volume">1</property><node><node bind="{volume}"/><text>{volume}</text></node></template>"#,
+        );
+        app.update();
+        app.update();
+
+        let entity = bound(&mut app);
+        assert_eq!(read(&mut app, entity).as_deref(), Some("1"));
+        assert_eq!(text(&mut app), ["1"]);
+
+        write(&mut app, entity, "5");
+        app.update();
+
+        assert_eq!(read(&mut app, entity).as_deref(), Some("5"));
+        let props = app.world().get::<TemplateProperties>(root).unwrap();
+        assert_eq!(props.get("volume").map(|v| v.as_str()), Some("5"));
+        assert_eq!(text(&mut app), ["5"]);
+    }
+
+    #[test]
+    fn test_write_reflected_binding() {
+        let mut app = test_app();
+        app.register_type::<Settings>().init_resource::<Settings>();
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><node bind="{res:Settings.volume}"/><text>{res:Settings.volume}</text></node></template>"#,
+        );
+        app.update();
+        app.update();
+
+        let entity = bound(&mut app);
+        assert_eq!(read(&mut app, entity).as_deref(), Some("0"));
+
+        write(&mut app, entity, "7");
+        app.update();
+
+        assert_eq!(app.world().resource::<Settings>().volume, 7);
+        assert_eq!(read(&mut app, entity).as_deref(), Some("7"));
+        assert_eq!(text(&mut app), ["7"]);
+    }
+
+    #[test]
+    fn test_write_unchanged_binding() {
+        let mut app = test_app();
+        app.init_resource::<Compiles>().add_observer(
+            |_: Trigger<CompileContentEvent>, mut compiles: ResMut<Compiles>| {
+                compiles.0 += 1;
+            },
+        );
+
+        spawn_template(
+            &mut app,
+            r#"<template><property name="volume">1</property><node><node bind="{volume}"/><text>{volume}</text></node></template>"#,
+        );
+        app.update();
+        app.update();
+
+        let entity = bound(&mut app);
+        let compiles = app.world().resource::<Compiles>().0;
+
+        write(&mut app, entity, "1");
+        app.update();
+        assert_eq!(app.world().resource::<Compiles>().0, compiles);
+
+        write(&mut app, entity, "2");
+        app.update();
+        assert!(app.world().resource::<Compiles>().0 > compiles);
+    }
+
+    #[test]
+    fn test_scroll_once_per_listener() {
+        let mut app = test_app();
+        app.init_resource::<Scrolls>();
+        let count = app
+            .world_mut()
+            .register_system(|_: In<Entity>, mut scrolls: ResMut<Scrolls>| scrolls.0 += 1);
+        app.world_mut()
+            .resource_mut::<FunctionBindings>()
+            .register("count", count);
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><node on_scroll="count"><node><node/></node></node></node></template>"#,
+        );
+        app.update();
+
+        let world = app.world_mut();
+        let listener = world
+            .query_filtered::<Entity, With<crate::prelude::OnUiScroll>>()
+            .single(world);
+        let child = world.get::<Children>(listener).unwrap()[0];
+        let grandchild = world.get::<Children>(child).unwrap()[0];
+
+        // nested hits of two pointers
+        let hit = HitData::new(listener, 0., None, None);
+        let hits = [child, grandchild]
+            .into_iter()
+            .map(|entity| (entity, hit.clone()))
+            .collect::<HashMap<_, _>>();
+        let mut hover_map = HoverMap::default();
+        hover_map.insert(PointerId::Mouse, hits.clone());
+        hover_map.insert(PointerId::Touch(0), hits);
+        world.insert_resource(hover_map);
+
+        world.send_event(MouseWheel {
+            unit: MouseScrollUnit::Pixel,
+            x: 0.,
+            y: 10.,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+
+        assert_eq!(app.world().resource::<Scrolls>().0, 1);
+    }
+}
@@ -1,15 +1,30 @@
 use crate::{
+    atlas::{AtlasLayouts, UiAtlasAnimation, DEFAULT_ATLAS_FPS},
     compile::CompileContextEvent,
-    data::{AttrTokens, HtmlTemplate, NodeType, XNode},
+    data::{
+        AttrTokens, Branch, FnCall, ForEach, HtmlStyleSheet, HtmlTemplate, NodeType, StyleAttr,
+        XNode,
+    },
+    expr::{parse_content, Expr},
+    focus::UiFocusable,
     prelude::ComponentBindings,
-    styles::{HoverTimer, HtmlStyle, PressedTimer},
+    reflect::{
+        is_reflected, reflected_expr_keys, reflected_keys, sync_new_reflect_bindings,
+        ReflectBindings,
+    },
+    shadow::TextShadowOf,
+    styles::{
+        EnterTimer, HoverTimer, HtmlStyle, PressedTimer, StateTimers, UiAnimation, UiDisabled,
+        UiStates,
+    },
+    theme::{HuiTheme, ThemeKeys},
     util::SlotId,
 };
-use bevy::{prelude::*, utils::HashMap};
-use nom::{
-    bytes::complete::{is_not, tag, take_until},
-    character::complete::multispace0,
-    sequence::{delimited, preceded, tuple},
+use bevy::{
+    ecs::{component::ComponentId, system::SystemParam},
+    prelude::*,
+    ui::FocusPolicy,
+    utils::{HashMap, HashSet},
 };
 use std::time::Duration;
 
@@ -27,15 +42,35 @@ impl Plugin for BuildPlugin {
             .register_type::<OnUiPress>()
             .register_type::<OnUiSpawn>()
             .register_type::<OnUiChange>()
+            .register_type::<OnUiRelease>()
+            .register_type::<OnUiClick>()
+            .register_type::<OnUiDoubleClick>()
+            .register_type::<OnUiRightClick>()
+            .register_type::<OnUiDragStart>()
+            .register_type::<OnUiDrag>()
+            .register_type::<OnUiDragEnd>()
+            .register_type::<OnUiScroll>()
+            .register_type::<OnUiFocus>()
+            .register_type::<OnUiBlur>()
             .register_type::<UiTarget>()
+            .register_type::<UiBind>()
             .register_type::<UiId>()
             .register_type::<SlotPlaceholder>()
+            .register_type::<UiSlot>()
             .register_type::<UnslotedChildren>()
+            .register_type::<TemplateFragments>()
             .register_type::<HtmlNode>()
             .register_type::<super::data::XNode>()
             .register_type::<super::data::HtmlTemplate>()
+            .register_type::<super::data::HtmlStyleSheet>()
             .register_type::<super::data::StyleAttr>()
-            .register_type::<super::data::Action>();
+            .register_type::<super::data::Action>()
+            .register_type::<super::data::FnCall>();
+
+        app.add_observer(expand_loop);
+        app.add_observer(update_branch);
+        app.add_observer(despawn_fragments);
+        app.add_observer(despawn_slot_holder);
     }
 }
 
@@ -75,18 +110,43 @@ pub struct InsideSlot {
 #[reflect]
 pub struct SlotPlaceholder {
     owner: Entity,
+    /// `<slot name="footer">`, `None` is the default slot
+    name: Option<String>,
 }
 
+/// marks a child passed to a component for
+/// a named slot. `slot="footer"`
+#[derive(Component, Debug, Clone, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct UiSlot(pub String);
+
+/// The additional root nodes of a fragment template,
+/// spawned as siblings after the `HtmlNode` entity.
+/// Despawned together with the template.
+#[derive(Component, Debug, Deref, Reflect)]
+#[reflect]
+pub struct TemplateFragments(Vec<Entity>);
+
 /// ref to unresolved nodes that
 /// need to move to the `<slot/>`
 /// when the template is loaded.
+/// Kept until a matching slot spawns,
+/// slots can be inside a branch or loop.
 #[derive(Component, Reflect, Debug)]
 #[reflect]
 pub struct UnslotedChildren(Entity);
 
+/// hidden node, holding the unsloted children
+fn slot_holder() -> Node {
+    Node {
+        display: Display::None,
+        ..default()
+    }
+}
+
 /// entities subscribed to the owners interaction
 /// component
-#[derive(Component, DerefMut, Debug, Deref)]
+#[derive(Component, DerefMut, Debug, Default, Deref)]
 pub struct InteractionObverser(Vec<Entity>);
 
 /// unresolved expresssions that can be compiled
@@ -123,31 +183,141 @@ pub struct UiWatch(pub Entity);
 #[derive(Component, Default)]
 pub struct FullyBuild;
 
+/// Two way binding of a widget value to a property
+/// of the owning template. `bind="{volume}"`
+///
+/// Use [`crate::prelude::HtmlBindings`] to read and write.
+#[derive(Component, Debug, Clone, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct UiBind(pub String);
+
+/// A `<for>` node, expands its children once
+/// per entry of the list property.
+///
+/// Each iteration owns a copy of the surrounding
+/// `TemplateProperties` with the current value under the
+/// loop binding and the position under `index`.
+#[derive(Component, Debug)]
+pub struct UiLoop {
+    each: ForEach,
+    body: Vec<XNode>,
+    items: Option<String>,
+}
+
+/// A conditional node, spawned as hidden placeholder
+/// and build in place, once its condition holds.
+#[derive(Component, Debug)]
+pub struct UiBranch {
+    /// conditions of the previous branches in the chain,
+    /// all have to fail.
+    previous: Vec<Expr>,
+    /// own condition, `None` for `<else>`
+    condition: Option<Expr>,
+    node: XNode,
+    scope: Entity,
+    active: bool,
+}
+
+impl UiBranch {
+    fn holds(&self, props: &TemplateProperties) -> bool {
+        let holds = |expr: &Expr| expr.eval(props).is_some_and(|v| v.is_truthy());
+        self.previous.iter().all(|c| !holds(c)) && self.condition.as_ref().is_none_or(holds)
+    }
+}
+
+#[derive(Bundle)]
+struct KeepBranch {
+    pub parent: Parent,
+    pub branch: UiBranch,
+    pub scope: TemplateScope,
+    pub reflected: ReflectBindings,
+    pub slot: UiSlot,
+    pub inside: InsideSlot,
+}
+
+/// the components of a template root with an `if`, before
+/// the branch was build into it. Only the components added
+/// by the branch are removed, when its condition fails.
+#[derive(Component)]
+struct RootBranchComponents(Vec<ComponentId>);
+
 /// Eventlistener interaction transition to Hover
 #[derive(Component, Debug, Deref, DerefMut, Reflect)]
 #[reflect]
-pub struct OnUiPress(pub Vec<String>);
+pub struct OnUiPress(pub Vec<FnCall>);
 
 /// Eventlistener on spawning node
 #[derive(Component, Debug, DerefMut, Deref, Reflect)]
 #[reflect]
-pub struct OnUiSpawn(pub Vec<String>);
+pub struct OnUiSpawn(pub Vec<FnCall>);
 
 /// Eventlistener for interaction transition to Hover
 #[derive(Component, Debug, DerefMut, Deref, Reflect)]
 #[reflect]
-pub struct OnUiEnter(pub Vec<String>);
+pub struct OnUiEnter(pub Vec<FnCall>);
 
 /// Eventlistener for interaction transition to None
 #[derive(Component, Debug, Deref, DerefMut, Reflect)]
 #[reflect]
-pub struct OnUiExit(pub Vec<String>);
+pub struct OnUiExit(pub Vec<FnCall>);
 
 /// Eventlistener for a user triggered Change Event
 /// This can be when building a widgets
 #[derive(Component, Debug, Deref, DerefMut, Reflect)]
 #[reflect]
-pub struct OnUiChange(pub Vec<String>);
+pub struct OnUiChange(pub Vec<FnCall>);
+
+/// Eventlistener for the primary button released over the node
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiRelease(pub Vec<FnCall>);
+
+/// Eventlistener for a primary button press and release on the same node
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiClick(pub Vec<FnCall>);
+
+/// Eventlistener for two clicks in quick succession
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiDoubleClick(pub Vec<FnCall>);
+
+/// Eventlistener for a secondary button click
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiRightClick(pub Vec<FnCall>);
+
+/// Eventlistener for the start of a drag
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiDragStart(pub Vec<FnCall>);
+
+/// Eventlistener while the node is dragged.
+/// The movement is stored in [crate::prelude::UiDrag]
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiDrag(pub Vec<FnCall>);
+
+/// Eventlistener for the end of a drag
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiDragEnd(pub Vec<FnCall>);
+
+/// Eventlistener for the mouse wheel over the node.
+/// The delta is stored in [crate::prelude::UiScroll]
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiScroll(pub Vec<FnCall>);
+
+/// Eventlistener for the node receiving focus
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiFocus(pub Vec<FnCall>);
+
+/// Eventlistener for the node losing focus
+#[derive(Component, Debug, Deref, DerefMut, Reflect)]
+#[reflect]
+pub struct OnUiBlur(pub Vec<FnCall>);
 
 /// Html Ui Node
 /// pass it a handle, it will spawn an UI.
@@ -159,31 +329,41 @@ pub struct HtmlNode(pub Handle<HtmlTemplate>);
 fn hotreload(
     mut cmd: Commands,
     mut events: EventReader<AssetEvent<HtmlTemplate>>,
+    mut sheet_events: EventReader<AssetEvent<HtmlStyleSheet>>,
+    assets: Res<Assets<HtmlTemplate>>,
     templates: Query<(Entity, &HtmlNode)>,
-    sloted_nodes: Query<(Entity, &InsideSlot)>,
+    sloted_nodes: Query<&InsideSlot>,
+    children: Query<&Children>,
 ) {
-    events.read().for_each(|ev| {
-        let id = match ev {
-            AssetEvent::Modified { id } => id,
-            _ => {
-                return;
-            }
+    let mut modified = events
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    // restyle templates using a modified stylesheet
+    sheet_events.read().for_each(|ev| {
+        let AssetEvent::Modified { id: sheet_id } = ev else {
+            return;
         };
 
+        modified.extend(assets.iter().filter_map(|(id, template)| {
+            template
+                .stylesheets
+                .iter()
+                .any(|sheet| sheet.id() == *sheet_id)
+                .then_some(id)
+        }));
+    });
+
+    modified.iter().for_each(|id| {
         templates
             .iter()
             .filter(|(_, html)| html.id() == *id)
             .for_each(|(entity, _)| {
-                let slots = sloted_nodes
-                    .iter()
-                    .flat_map(|(slot_entity, slot)| (slot.owner == entity).then_some(slot_entity))
-                    .collect::<Vec<_>>();
-
-                if slots.len() > 0 {
-                    let slot_holder = cmd.spawn_empty().add_children(&slots).id();
-                    cmd.entity(entity).insert(UnslotedChildren(slot_holder));
-                }
-
+                park_sloted(&mut cmd, entity, &children, &sloted_nodes);
                 cmd.entity(entity)
                     .despawn_descendants()
                     .retain::<KeepComps>();
@@ -200,51 +380,192 @@ struct KeepComps {
     pub slot: SlotPlaceholder,
     pub inside: InsideSlot,
     pub scope: TemplateScope,
+    pub bind: UiBind,
+    pub reflected: ReflectBindings,
+}
+
+/// the descendants of a node and the slot
+/// content, that was moved into them.
+#[derive(SystemParam)]
+struct SlotedHierarchy<'w, 's> {
+    children: Query<'w, 's, &'static Children>,
+    sloted: Query<'w, 's, &'static InsideSlot>,
+}
+
+/// moves slot content out of the descendants of `entity`,
+/// that are about to be despawned, back into the holder
+/// of their owner. Content of owners, that are despawned
+/// as well, is dropped.
+fn park_sloted(
+    cmd: &mut Commands,
+    entity: Entity,
+    children: &Query<&Children>,
+    sloted_nodes: &Query<&InsideSlot>,
+) {
+    let removed = children.iter_descendants(entity).collect::<HashSet<_>>();
+    let mut owners: HashMap<Entity, Vec<Entity>> = HashMap::default();
+    removed.iter().for_each(|child| {
+        let Ok(slot) = sloted_nodes.get(*child) else {
+            return;
+        };
+        if !removed.contains(&slot.owner) {
+            owners.entry(slot.owner).or_default().push(*child);
+        }
+    });
+
+    for (owner, content) in owners {
+        cmd.queue(move |world: &mut World| {
+            if world.get_entity(owner).is_err() {
+                return;
+            }
+
+            let holder = match world.get::<UnslotedChildren>(owner) {
+                Some(holder) => holder.0,
+                None => {
+                    let holder = world.spawn(slot_holder()).id();
+                    world.entity_mut(owner).insert(UnslotedChildren(holder));
+                    holder
+                }
+            };
+
+            world.entity_mut(holder).add_children(&content);
+        });
+    }
+}
+
+/// despawns the hidden slot holder with its owner
+fn despawn_slot_holder(
+    trigger: Trigger<OnRemove, UnslotedChildren>,
+    mut cmd: Commands,
+    holders: Query<&UnslotedChildren>,
+) {
+    let Ok(holder) = holders.get(trigger.entity()) else {
+        return;
+    };
+
+    if let Some(entity) = cmd.get_entity(holder.0) {
+        entity.try_despawn_recursive();
+    }
+}
+
+/// true, if the nodes contain a slot with the name,
+/// including slots inside branches and loops.
+fn has_slot(nodes: &[XNode], name: Option<&String>) -> bool {
+    nodes.iter().any(|node| {
+        (matches!(node.node_type, NodeType::Slot) && node.name.as_ref() == name)
+            || has_slot(&node.children, name)
+    })
 }
 
 fn move_children_to_slot(
     mut cmd: Commands,
-    unsloted_includes: Query<(Entity, &UnslotedChildren)>,
+    new_slots: Query<(Entity, &SlotPlaceholder, &Parent), Added<SlotPlaceholder>>,
+    unsloted_includes: Query<&UnslotedChildren>,
     children: Query<&Children>,
-    slots: Query<(Entity, &SlotPlaceholder)>,
-    parent: Query<&Parent>,
+    slot_names: Query<&UiSlot>,
+    templates: TemplateLookup,
+    text_shadows: Query<(), With<TextShadowOf>>,
 ) {
-    unsloted_includes
-        .iter()
-        .for_each(|(entity, UnslotedChildren(slot_holder))| {
-            let Some(placeholder_entity) = slots
-                .iter()
-                .find_map(|(slot_ent, slot)| (slot.owner == entity).then_some(slot_ent))
-            else {
-                return;
-            };
+    let mut owners: HashMap<Entity, Vec<(Entity, &SlotPlaceholder, Entity)>> = HashMap::default();
+    new_slots.iter().for_each(|(slot_entity, slot, parent)| {
+        owners
+            .entry(slot.owner)
+            .or_default()
+            .push((slot_entity, slot, parent.get()));
+    });
 
-            let Ok(slot_parent) = parent.get(placeholder_entity).map(|p| p.get()) else {
-                error!("parentless slot, impossible");
-                return;
+    owners.into_iter().for_each(|(owner, slots)| {
+        let slot_holder = unsloted_includes.get(owner).ok().map(|holder| holder.0);
+        let mut unsloted = slot_holder
+            .and_then(|holder| children.get(holder).ok())
+            .map(|children| children.iter().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        // text shadows follow their text
+        unsloted.retain(|child| !text_shadows.contains(*child));
+
+        for (placeholder_entity, slot, slot_parent) in slots {
+            let (content, rest): (Vec<_>, Vec<_>) = unsloted.into_iter().partition(|child| {
+                slot_names.get(*child).ok().map(|name| &name.0) == slot.name.as_ref()
+            });
+            unsloted = rest;
+
+            let content = if content.is_empty() {
+                // fallback content of the slot
+                children
+                    .get(placeholder_entity)
+                    .map(|children| children.iter().copied().collect::<Vec<_>>())
+                    .unwrap_or_default()
+            } else {
+                for child in content.iter() {
+                    cmd.entity(*child).insert(InsideSlot { owner });
+                }
+                content
             };
 
-            _ = children.get(*slot_holder).map(|children| {
-                children.iter().for_each(|child| {
-                    if *child != slot_parent {
-                        cmd.entity(*child).insert(InsideSlot { owner: entity });
-                        cmd.entity(slot_parent).add_child(*child);
-                    }
-                })
+            let content = content
+                .into_iter()
+                .filter(|child| *child != slot_parent)
+                .collect::<Vec<_>>();
+
+            // earlier slots of the same parent move the placeholder,
+            // the index is only known, when the content is inserted.
+            cmd.queue(move |world: &mut World| {
+                let index = world
+                    .get::<Children>(slot_parent)
+                    .and_then(|siblings| siblings.iter().position(|e| *e == placeholder_entity))
+                    .unwrap_or_default();
+
+                if let Ok(mut parent) = world.get_entity_mut(slot_parent) {
+                    parent.insert_children(index, &content);
+                }
+
+                if let Ok(placeholder) = world.get_entity_mut(placeholder_entity) {
+                    placeholder.despawn_recursive();
+                }
             });
+        }
+
+        // keep children of slots, that are not spawned yet
+        let template = templates.of(owner);
+
+        let (waiting, dropped): (Vec<_>, Vec<_>) = unsloted.into_iter().partition(|child| {
+            let name = slot_names.get(*child).ok().map(|name| &name.0);
+            template.is_none_or(|template| has_slot(&template.root, name))
+        });
 
-            cmd.entity(entity).remove::<UnslotedChildren>();
-            cmd.entity(placeholder_entity).despawn_recursive();
-            cmd.entity(*slot_holder).despawn();
+        dropped.iter().for_each(|child| {
+            let name = slot_names
+                .get(*child)
+                .map(|n| n.as_str())
+                .unwrap_or("default");
+            warn!("component {owner} has no `{name}` slot, dropping {child}");
+            cmd.entity(*child).despawn_recursive();
         });
+
+        if slot_holder.is_some() && waiting.is_empty() {
+            cmd.entity(owner).remove::<UnslotedChildren>();
+        }
+    });
+}
+
+/// the assets and resources a [`TemplateBuilder`] needs
+#[derive(SystemParam)]
+struct BuildResources<'w> {
+    sheets: Res<'w, Assets<HtmlStyleSheet>>,
+    server: Res<'w, AssetServer>,
+    atlas_layouts: ResMut<'w, AtlasLayouts>,
+    custom_comps: Res<'w, ComponentBindings>,
 }
 
 fn spawn_ui(
     mut cmd: Commands,
     mut unbuild: Query<(Entity, &HtmlNode, &mut TemplateProperties), Without<FullyBuild>>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
     assets: Res<Assets<HtmlTemplate>>,
-    server: Res<AssetServer>,
-    custom_comps: Res<ComponentBindings>,
+    mut resources: BuildResources,
+    theme: Res<HuiTheme>,
 ) {
     unbuild
         .iter_mut()
@@ -253,41 +574,73 @@ fn spawn_ui(
                 return;
             };
 
+            // wait for `<style src=".."/>`
+            if template.stylesheets.iter().any(|sheet| {
+                !resources.sheets.contains(sheet) && !resources.server.load_state(sheet).is_failed()
+            }) {
+                return;
+            }
+
             template.properties.iter().for_each(|(key, val)| {
                 _ = state.try_insert(key.to_owned(), val.clone());
             });
 
+            let mut theme_keys = ThemeKeys::default();
+            theme.apply(&mut theme_keys, &mut state);
+            cmd.entity(root_entity).insert(theme_keys);
+
+            if template.root.is_empty() {
+                warn!("template has no root node!");
+                return;
+            }
+
             let mut builder = TemplateBuilder::new(
                 root_entity,
                 cmd.reborrow(),
-                &server,
-                &custom_comps,
-                &template,
+                &resources.server,
+                &resources.sheets,
+                &mut resources.atlas_layouts,
+                &resources.custom_comps,
+                template,
             );
 
-            if let Some(node) = template.root.first() {
-                builder.build_tree(node);
-                builder.finalize_relations();
-                cmd.trigger_targets(CompileContextEvent, root_entity);
-            } else {
-                warn!("template has no root node!");
-            }
+            // the first root is build into the template entity,
+            // the rest are fragments next to it.
+            let fragments = builder.build_siblings(Some(root_entity), &template.root);
+            builder.finalize_relations();
 
-            if template.root.len() > 1 {
-                warn!("templates currently only support one root node, ignoring the rest");
+            let fragments = fragments.into_iter().skip(1).collect::<Vec<_>>();
+            if !fragments.is_empty() {
+                if let Ok(parent) = parents.get(root_entity).map(|p| p.get()) {
+                    let index = children
+                        .get(parent)
+                        .ok()
+                        .and_then(|siblings| siblings.iter().position(|e| *e == root_entity))
+                        .map(|index| index + 1)
+                        .unwrap_or_default();
+                    cmd.entity(parent).insert_children(index, &fragments);
+                }
+
+                cmd.entity(root_entity).insert(TemplateFragments(fragments));
             }
+
+            cmd.trigger_targets(CompileContextEvent, root_entity);
         });
 }
 
 struct TemplateBuilder<'w, 's> {
     cmd: Commands<'w, 's>,
     server: &'w AssetServer,
+    sheets: &'w Assets<HtmlStyleSheet>,
+    atlas_layouts: &'w mut AtlasLayouts,
     scope: Entity,
     comps: &'w ComponentBindings,
     subscriber: TemplatePropertySubscriber,
     ids: HashMap<String, Entity>,
     targets: HashMap<Entity, String>,
     watch: HashMap<String, Vec<Entity>>,
+    /// font styles of the `<text>`, inherited by its spans
+    text_styles: Vec<StyleAttr>,
     template: &'w HtmlTemplate,
 }
 
@@ -296,6 +649,8 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
         scope: Entity,
         cmd: Commands<'w, 's>,
         server: &'w AssetServer,
+        sheets: &'w Assets<HtmlStyleSheet>,
+        atlas_layouts: &'w mut AtlasLayouts,
         comps: &'w ComponentBindings,
         template: &'w HtmlTemplate,
     ) -> Self {
@@ -303,12 +658,15 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             cmd,
             scope,
             server,
+            sheets,
+            atlas_layouts,
             comps,
             template,
             subscriber: Default::default(),
             ids: Default::default(),
             targets: Default::default(),
             watch: Default::default(),
+            text_styles: Default::default(),
         }
     }
 
@@ -330,24 +688,153 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             .iter()
             .for_each(|(target_str, obs_list)| match self.ids.get(target_str) {
                 Some(to_observe) => {
+                    let obs_list = obs_list.clone();
                     self.cmd
                         .entity(*to_observe)
-                        .insert(InteractionObverser(obs_list.clone()));
+                        .entry::<InteractionObverser>()
+                        .or_default()
+                        .and_modify(move |mut obs| obs.extend(obs_list));
                 }
                 None => warn!("undefined watch target `{target_str}`"),
             });
 
+        // merge, branches build into an existing scope
+        let subscriber = std::mem::take(&mut self.subscriber);
         self.cmd
             .entity(self.scope)
-            .insert((std::mem::take(&mut self.subscriber), FullyBuild));
+            .insert_if_new(TemplatePropertySubscriber::default())
+            .queue(move |mut entity: EntityWorldMut| {
+                if let Some(mut existing) = entity.get_mut::<TemplatePropertySubscriber>() {
+                    for sub in subscriber.iter() {
+                        if !existing.contains(sub) {
+                            existing.push(*sub);
+                        }
+                    }
+                }
+            })
+            .insert(FullyBuild);
+
+        // resolve new reflected values, before the first compile
+        self.cmd.queue(sync_new_reflect_bindings);
     }
 
     pub fn build_tree(&mut self, root: &XNode) {
-        self.build_node(self.scope, root);
+        self.build_siblings(Some(self.scope), std::slice::from_ref(root));
+    }
+
+    fn build_children(&mut self, parent: Entity, children: &[XNode]) -> Vec<Entity> {
+        let entities = self.build_siblings(None, children);
+        self.cmd.entity(parent).add_children(&entities);
+        entities
+    }
+
+    /// builds a list of sibling nodes, `if` chains continue
+    /// across siblings. The first node is build into `first`,
+    /// if given.
+    fn build_siblings(&mut self, first: Option<Entity>, nodes: &[XNode]) -> Vec<Entity> {
+        let mut chain: Vec<Expr> = vec![];
+        let mut entities = vec![];
+        for (index, node) in nodes.iter().enumerate() {
+            let entity = match first {
+                Some(first) if index == 0 => first,
+                _ => self.cmd.spawn_empty().id(),
+            };
+            if let Some(slot) = &node.slot {
+                self.cmd.entity(entity).insert(UiSlot(slot.clone()));
+            }
+            match &node.branch {
+                Some(Branch::If(condition)) => {
+                    chain.clear();
+                    self.build_branch(entity, node, vec![], Some(condition.clone()));
+                    chain.push(condition.clone());
+                }
+                Some(Branch::ElseIf(condition)) => {
+                    self.build_branch(entity, node, chain.clone(), Some(condition.clone()));
+                    chain.push(condition.clone());
+                }
+                Some(Branch::Else) => {
+                    self.build_branch(entity, node, std::mem::take(&mut chain), None);
+                }
+                None => {
+                    chain.clear();
+                    self.build_node(entity, node);
+                }
+            }
+            entities.push(entity);
+        }
+        entities
+    }
+
+    fn build_branch(
+        &mut self,
+        entity: Entity,
+        node: &XNode,
+        previous: Vec<Expr>,
+        condition: Option<Expr>,
+    ) {
+        if entity != self.scope {
+            self.cmd.entity(entity).insert(TemplateScope(self.scope));
+            self.subscriber.push(entity);
+        }
+
+        ReflectBindings::bind(
+            self.cmd.entity(entity),
+            reflected_expr_keys(previous.iter().chain(condition.iter())),
+        );
+
+        self.cmd.entity(entity).insert((
+            Node {
+                display: Display::None,
+                ..default()
+            },
+            UiBranch {
+                previous,
+                condition,
+                node: XNode {
+                    branch: None,
+                    ..node.clone()
+                },
+                scope: self.scope,
+                active: false,
+            },
+        ));
+    }
+
+    /// templated text compiles on property changes,
+    /// static text only resolves its `{{` escapes.
+    fn text_content(&mut self, entity: Entity, node: &XNode, content: String) -> String {
+        if is_templated(&content) {
+            self.cmd.entity(entity).insert(ContentId(node.content_id));
+            self.subscriber.push(entity);
+            return content;
+        }
+
+        parse_content(&content)
+            .map(|content| content.eval_lossy(&TemplateProperties::default()))
+            .unwrap_or(content)
     }
 
     fn build_node(&mut self, entity: Entity, node: &XNode) {
-        let styles = HtmlStyle::from(node.styles.clone());
+        // inherited font first, then stylesheet rules, inline attributes win
+        let mut styles = match node.node_type {
+            NodeType::Span => self.text_styles.clone(),
+            _ => vec![],
+        };
+        styles.extend(self.template.matching_styles(node, self.sheets));
+        styles.extend(node.styles.iter().cloned());
+
+        let font_styles = styles
+            .iter()
+            .filter(|style| {
+                matches!(
+                    style,
+                    StyleAttr::Font(_) | StyleAttr::FontSize(_) | StyleAttr::FontColor(_)
+                )
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let styles = HtmlStyle::from(styles);
 
         // ----------------------
         // timers
@@ -358,7 +845,16 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             )))
             .insert(HoverTimer::new(Duration::from_secs_f32(
                 styles.computed.delay.max(0.01),
-            )));
+            )))
+            .insert((UiStates::default(), StateTimers::default()));
+
+        if !styles.enter.is_empty() {
+            self.cmd
+                .entity(entity)
+                .insert(EnterTimer::new(Duration::from_secs_f32(
+                    styles.computed.delay.max(0.01),
+                )));
+        }
 
         // ---------------------
         // shadow
@@ -379,6 +875,19 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             self.subscriber.push(entity);
         }
 
+        // ----------------------
+        // reflected sources
+        ReflectBindings::bind(self.cmd.entity(entity), reflected_keys(node, self.template));
+
+        // ----------------------
+        // two way binding
+        if let Some(bind) = &node.bind {
+            self.cmd.entity(entity).insert(UiBind(bind.clone()));
+            if is_reflected(bind) {
+                ReflectBindings::bind(self.cmd.entity(entity), vec![bind.clone()]);
+            }
+        }
+
         // ----------------------
         //tags
         self.cmd.entity(entity).insert(Tags(node.tags.clone()));
@@ -403,6 +912,34 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             };
         }
 
+        // ----------------------
+        // focus
+        let focusable = node
+            .focusable
+            .unwrap_or(matches!(node.node_type, NodeType::Button) || node.tab_index.is_some());
+        if focusable {
+            self.cmd.entity(entity).insert(UiFocusable {
+                tab_index: node.tab_index.unwrap_or_default(),
+            });
+        }
+
+        if node.disabled.unwrap_or_default() {
+            self.cmd.entity(entity).insert(UiDisabled);
+        }
+
+        // ----------------------
+        // keyframe animation
+        if let Some(animation) = &node.animation {
+            match self.template.keyframes.get(&animation.name) {
+                Some(keyframes) => {
+                    self.cmd
+                        .entity(entity)
+                        .insert(UiAnimation::new(animation.clone(), keyframes.clone()));
+                }
+                None => warn!("keyframes `{}` do not exist", animation.name),
+            }
+        }
+
         // ----------------------
         // events
         node.event_listener.iter().for_each(|listener| {
@@ -415,6 +952,10 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             self.cmd.entity(entity).insert(outline.clone());
         }
 
+        if let Some(z_index) = styles.computed.global_z_index {
+            self.cmd.entity(entity).insert(GlobalZIndex(z_index));
+        }
+
         match &node.node_type {
             // --------------------------------
             // div node
@@ -424,6 +965,21 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             // --------------------------------
             // spawn image
             NodeType::Image => {
+                let texture_atlas = node.atlas_layout.map(|layout| TextureAtlas {
+                    layout: self.atlas_layouts.get_or_add(layout, self.server),
+                    index: node
+                        .atlas_index
+                        .or(node.atlas_frames.map(|frames| frames.first))
+                        .unwrap_or_default(),
+                });
+
+                if node.atlas_frames.is_some() || node.fps.is_some() {
+                    self.cmd.entity(entity).insert(UiAtlasAnimation::new(
+                        node.atlas_frames,
+                        node.fps.unwrap_or(DEFAULT_ATLAS_FPS),
+                    ));
+                }
+
                 self.cmd.entity(entity).insert((
                     ImageNode {
                         image: node
@@ -438,7 +994,10 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
                             .cloned()
                             .unwrap_or_default(),
                         rect: styles.computed.image_region.clone(),
-                        ..default()
+                        color: styles.computed.image_color,
+                        flip_x: styles.computed.flip_x,
+                        flip_y: styles.computed.flip_y,
+                        texture_atlas,
                     },
                     styles,
                 ));
@@ -453,12 +1012,41 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
                     .map(|t| t.trim().to_string())
                     .unwrap_or_default();
 
-                if is_templated(&content) {
-                    self.cmd.entity(entity).insert(ContentId(node.content_id));
-                    self.subscriber.push(entity);
+                let content = self.text_content(entity, node, content);
+                self.cmd.entity(entity).insert((Text(content), styles));
+
+                if !node.children.is_empty() {
+                    let previous = std::mem::replace(&mut self.text_styles, font_styles);
+                    let spans = self.build_children(entity, &node.children);
+                    self.text_styles = previous;
+
+                    // spans follow the interaction of their text
+                    self.cmd
+                        .entity(entity)
+                        .insert((Interaction::default(), FocusPolicy::Pass))
+                        .entry::<InteractionObverser>()
+                        .or_default()
+                        .and_modify(move |mut obs| obs.extend(spans));
                 }
+                return;
+            }
+            // --------------------------------
+            // spawn inline text
+            NodeType::Span => {
+                let content = self
+                    .template
+                    .content
+                    .get(node.content_id)
+                    .cloned()
+                    .unwrap_or_default();
 
-                self.cmd.entity(entity).insert((Text(content), styles));
+                let content = self.text_content(entity, node, content);
+                self.cmd.entity(entity).insert((TextSpan(content), styles));
+
+                let previous = std::mem::replace(&mut self.text_styles, font_styles);
+                self.build_children(entity, &node.children);
+                self.text_styles = previous;
+                return;
             }
             // --------------------------------
             // spawn button
@@ -469,12 +1057,8 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
                 // mark children
                 self.comps.try_spawn(custom, entity, &mut self.cmd);
                 if node.children.len() > 0 {
-                    let slot_holder = self.cmd.spawn(Node::default()).id();
-                    for child_node in node.children.iter() {
-                        let child_entity = self.cmd.spawn_empty().id();
-                        self.build_node(child_entity, child_node);
-                        self.cmd.entity(slot_holder).add_child(child_entity);
-                    }
+                    let slot_holder = self.cmd.spawn(slot_holder()).id();
+                    self.build_children(slot_holder, &node.children);
 
                     self.cmd
                         .entity(entity)
@@ -492,11 +1076,39 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
                 return;
             }
             // --------------------------------
-            // spawn slot
+            // spawn slot, children are the fallback content
             NodeType::Slot => {
-                self.cmd
-                    .entity(entity)
-                    .insert((Node::default(), SlotPlaceholder { owner: self.scope }));
+                self.cmd.entity(entity).insert((
+                    Node::default(),
+                    SlotPlaceholder {
+                        owner: self.scope,
+                        name: node.name.clone(),
+                    },
+                ));
+            }
+            // --------------------------------
+            // spawn loop, expanded on compile
+            NodeType::For => {
+                let Some(each) = node.each.clone() else {
+                    warn!("`<for>` node without `each` attribute, nothing to repeat");
+                    return;
+                };
+
+                self.cmd.entity(entity).insert((
+                    Node::default(),
+                    styles,
+                    UiLoop {
+                        each,
+                        body: node.children.clone(),
+                        items: None,
+                    },
+                ));
+
+                if !self.subscriber.contains(&entity) {
+                    self.subscriber.push(entity);
+                }
+
+                return;
             }
             // --------------------------------
             // don't render
@@ -505,20 +1117,499 @@ impl<'w, 's> TemplateBuilder<'w, 's> {
             }
         };
 
-        for child in node.children.iter() {
-            let child_entity = self.cmd.spawn_empty().id();
-            self.build_node(child_entity, child);
-            self.cmd.entity(entity).add_child(child_entity);
+        self.build_children(entity, &node.children);
+    }
+}
+
+/// despawns the fragment siblings together
+/// with the template entity.
+fn despawn_fragments(
+    trigger: Trigger<OnRemove, TemplateFragments>,
+    mut cmd: Commands,
+    fragments: Query<&TemplateFragments>,
+) {
+    let Ok(fragments) = fragments.get(trigger.entity()) else {
+        return;
+    };
+
+    for fragment in fragments.iter() {
+        if let Some(entity) = cmd.get_entity(*fragment) {
+            entity.try_despawn_recursive();
         }
     }
 }
 
-//@todo:dirty AF
+/// the templates of the live component roots
+#[derive(SystemParam)]
+pub(crate) struct TemplateLookup<'w, 's> {
+    html_nodes: Query<'w, 's, &'static HtmlNode>,
+    scopes: Query<'w, 's, &'static TemplateScope>,
+    assets: Res<'w, Assets<HtmlTemplate>>,
+}
+
+impl TemplateLookup<'_, '_> {
+    /// the template this component root was build from
+    pub fn of(&self, entity: Entity) -> Option<&HtmlTemplate> {
+        self.html_nodes
+            .get(entity)
+            .ok()
+            .and_then(|html| self.assets.get(&**html))
+    }
+
+    /// the template any node was build from
+    pub fn find(&self, entity: Entity) -> Option<&HtmlTemplate> {
+        find_template(entity, &self.scopes, &self.html_nodes)
+            .and_then(|html| self.assets.get(&**html))
+    }
+
+    /// the entity owning the properties of this node
+    pub fn scope(&self, entity: Entity) -> Option<Entity> {
+        self.scopes.get(entity).ok().map(|scope| **scope)
+    }
+}
+
+/// walks up the scope chain, until the template
+/// this node was build from is found.
+pub(crate) fn find_template<'a>(
+    entity: Entity,
+    scopes: &Query<&TemplateScope>,
+    html_nodes: &'a Query<&HtmlNode>,
+) -> Option<&'a HtmlNode> {
+    let mut current = entity;
+    loop {
+        if let Ok(html) = html_nodes.get(current) {
+            return Some(html);
+        }
+        current = **scopes.get(current).ok()?;
+    }
+}
+
+/// (re)builds the iterations of a `<for>` node, when
+/// the list property changed. Otherwise passes the
+/// context down to the existing iterations.
+fn expand_loop(
+    trigger: Trigger<CompileContextEvent>,
+    mut cmd: Commands,
+    mut loops: Query<(&mut UiLoop, Option<&Children>)>,
+    mut properties: Query<&mut TemplateProperties>,
+    hierarchy: SlotedHierarchy,
+    templates: TemplateLookup,
+    mut resources: BuildResources,
+) {
+    let entity = trigger.entity();
+    let Ok((mut ui_loop, children)) = loops.get_mut(entity) else {
+        return;
+    };
+
+    let Some((context_entity, context)) = properties
+        .get(entity)
+        .ok()
+        .map(|props| (entity, props.clone()))
+        .or_else(|| {
+            let scope = templates.scope(entity)?;
+            properties
+                .get(scope)
+                .ok()
+                .map(|props| (scope, props.clone()))
+        })
+    else {
+        warn!("loop {entity} has no context scope");
+        return;
+    };
+
+    let items = context
+        .get(&ui_loop.each.source)
+        .cloned()
+        .unwrap_or_default();
+
+    if ui_loop.items.as_ref() == Some(&items) {
+        for child in children.map(|c| c.iter()).into_iter().flatten() {
+            let Ok(mut props) = properties.get_mut(*child) else {
+                continue;
+            };
+
+            props.extend(
+                context
+                    .iter()
+                    .filter(|(key, _)| **key != ui_loop.each.binding && *key != "index")
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+            cmd.trigger_targets(CompileContextEvent, *child);
+        }
+        return;
+    }
+
+    let Some(template) = templates.find(entity) else {
+        warn!("loop {entity} is not part of a template");
+        return;
+    };
+
+    park_sloted(&mut cmd, entity, &hierarchy.children, &hierarchy.sloted);
+    cmd.entity(entity).despawn_descendants();
+
+    let values = items
+        .split(',')
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty());
+
+    for (index, value) in values.enumerate() {
+        for node in ui_loop.body.iter() {
+            let iteration = cmd
+                .spawn((
+                    context
+                        .clone()
+                        .with(&ui_loop.each.binding, value)
+                        .with("index", &index.to_string()),
+                    TemplateScope(context_entity),
+                ))
+                .id();
+
+            let mut builder = TemplateBuilder::new(
+                iteration,
+                cmd.reborrow(),
+                &resources.server,
+                &resources.sheets,
+                &mut resources.atlas_layouts,
+                &resources.custom_comps,
+                template,
+            );
+
+            builder.build_tree(node);
+            builder.finalize_relations();
+
+            cmd.entity(entity).add_child(iteration);
+            cmd.trigger_targets(CompileContextEvent, iteration);
+        }
+    }
+
+    ui_loop.items = Some(items);
+}
+
+/// spawns or despawns a conditional node, when
+/// the outcome of its condition changed.
+fn update_branch(
+    trigger: Trigger<CompileContextEvent>,
+    mut cmd: Commands,
+    mut branches: Query<&mut UiBranch>,
+    properties: Query<&TemplateProperties>,
+    hierarchy: SlotedHierarchy,
+    templates: TemplateLookup,
+    mut resources: BuildResources,
+) {
+    let entity = trigger.entity();
+    let Ok(mut branch) = branches.get_mut(entity) else {
+        return;
+    };
+
+    let Some(props) = properties.get(branch.scope).ok() else {
+        warn!("branch {entity} has no context scope");
+        return;
+    };
+
+    let holds = branch.holds(props);
+    if holds == branch.active {
+        return;
+    }
+
+    branch.active = holds;
+
+    if !holds {
+        let removed = hierarchy
+            .children
+            .iter_descendants(entity)
+            .collect::<Vec<_>>();
+        cmd.entity(branch.scope)
+            .queue(move |mut scope: EntityWorldMut| {
+                if let Some(mut subs) = scope.get_mut::<TemplatePropertySubscriber>() {
+                    subs.retain(|sub| !removed.contains(sub));
+                }
+            });
+
+        park_sloted(&mut cmd, entity, &hierarchy.children, &hierarchy.sloted);
+        let mut entity_cmd = cmd.entity(entity);
+        entity_cmd.despawn_descendants();
+        if branch.scope == entity {
+            entity_cmd.queue(|mut entity: EntityWorldMut| {
+                let Some(keep) = entity.take::<RootBranchComponents>() else {
+                    return;
+                };
+
+                let added = entity
+                    .archetype()
+                    .components()
+                    .filter(|id| !keep.0.contains(id))
+                    .collect::<Vec<_>>();
+
+                for id in added {
+                    entity.remove_by_id(id);
+                }
+            });
+        } else {
+            entity_cmd.retain::<KeepBranch>();
+        }
+        entity_cmd.insert(Node {
+            display: Display::None,
+            ..default()
+        });
+        return;
+    }
+
+    let Some(template) = templates.find(entity) else {
+        warn!("branch {entity} is not part of a template");
+        return;
+    };
+
+    if branch.scope == entity {
+        cmd.entity(entity).queue(|mut entity: EntityWorldMut| {
+            let components = entity.archetype().components().collect();
+            entity.insert(RootBranchComponents(components));
+        });
+    }
+
+    cmd.entity(entity).insert(Node::default());
+
+    let mut builder = TemplateBuilder::new(
+        branch.scope,
+        cmd.reborrow(),
+        &resources.server,
+        &resources.sheets,
+        &mut resources.atlas_layouts,
+        &resources.custom_comps,
+        template,
+    );
+
+    builder.build_node(entity, &branch.node);
+    builder.finalize_relations();
+
+    cmd.trigger_targets(CompileContextEvent, branch.scope);
+}
+
+/// true, if the text has at least one placeholder.
 pub fn is_templated(input: &str) -> bool {
-    let parts: Result<(&str, (&str, &str)), nom::Err<nom::error::Error<&str>>> = tuple((
-        take_until("{"),
-        delimited(tag("{"), preceded(multispace0, is_not("}")), tag("}")),
-    ))(input);
+    parse_content(input).is_some_and(|content| content.has_placeholders())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{add_template, spawn_template, test_app};
+
+    #[test]
+    fn test_slot_inside_branch() {
+        let mut app = test_app();
+        let card = add_template(
+            &mut app,
+            r#"<template><property name="open">false</property><node><slot/><node if="{open}"><slot name="footer"/></node></node></template>"#,
+        );
+        app.world_mut()
+            .resource_mut::<ComponentBindings>()
+            .register("card", move |mut cmd| {
+                cmd.insert(HtmlNode(card.clone()));
+            });
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><card><text>body</text><text slot="footer">footer</text></card></node></template>"#,
+        );
+
+        let find = |app: &mut App, content: &str| {
+            app.world_mut()
+                .query::<(Entity, &Text)>()
+                .iter(app.world())
+                .find_map(|(entity, text)| (text.0 == content).then_some(entity))
+        };
+
+        let card = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<Entity, (With<HtmlNode>, With<Parent>)>()
+                .single(app.world())
+        };
 
-    parts.is_ok()
+        let inside_card = |app: &mut App, entity: Entity| {
+            let card = card(app);
+            let world = app.world();
+            let mut current = entity;
+            while let Some(parent) = world.get::<Parent>(current) {
+                current = parent.get();
+                if current == card {
+                    return true;
+                }
+            }
+            false
+        };
+
+        let set_open = |app: &mut App, open: &str| {
+            let card = card(app);
+            app.world_mut()
+                .get_mut::<TemplateProperties>(card)
+                .unwrap()
+                .insert("open".into(), open.into());
+            app.world_mut().trigger_targets(CompileContextEvent, card);
+            app.update();
+            app.update();
+        };
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let body = find(&mut app, "body").unwrap();
+        assert!(inside_card(&mut app, body));
+        let footer = find(&mut app, "footer").unwrap();
+        assert!(!inside_card(&mut app, footer));
+
+        set_open(&mut app, "true");
+        let footer = find(&mut app, "footer").unwrap();
+        assert!(inside_card(&mut app, footer));
+
+        set_open(&mut app, "false");
+        let footer = find(&mut app, "footer").unwrap();
+        assert!(!inside_card(&mut app, footer));
+
+        set_open(&mut app, "true");
+        let footer = find(&mut app, "footer").unwrap();
+        assert!(inside_card(&mut app, footer));
+    }
+
+    #[derive(Component)]
+    struct UserMarker;
+
+    #[test]
+    fn test_root_branch_keeps_user_components() {
+        let mut app = test_app();
+        let root = spawn_template(
+            &mut app,
+            r#"<template><property name="open">true</property><node if="{open}" width="10px"><text>inside</text></node></template>"#,
+        );
+        app.world_mut()
+            .entity_mut(root)
+            .insert((Name::new("menu"), UserMarker));
+
+        app.update();
+        app.update();
+        assert!(app.world().get::<HtmlStyle>(root).is_some());
+
+        app.world_mut()
+            .get_mut::<TemplateProperties>(root)
+            .unwrap()
+            .insert("open".into(), "false".into());
+        app.world_mut().trigger_targets(CompileContextEvent, root);
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<HtmlStyle>(root).is_none());
+        assert!(world.get::<Children>(root).is_none());
+        assert_eq!(world.get::<Node>(root).unwrap().display, Display::None);
+        assert!(world.get::<UserMarker>(root).is_some());
+        assert_eq!(world.get::<Name>(root).unwrap().as_str(), "menu");
+        assert!(world.get::<HtmlNode>(root).is_some());
+
+        app.world_mut()
+            .get_mut::<TemplateProperties>(root)
+            .unwrap()
+            .insert("open".into(), "true".into());
+        app.world_mut().trigger_targets(CompileContextEvent, root);
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<HtmlStyle>(root).is_some());
+        assert!(world.get::<UserMarker>(root).is_some());
+    }
+
+    #[test]
+    fn test_multiple_slots_keep_order() {
+        let mut app = test_app();
+        let card = add_template(
+            &mut app,
+            r#"<template><node><slot name="header"><text>d1</text></slot><slot name="body"><text>d2</text></slot></node></template>"#,
+        );
+        app.world_mut()
+            .resource_mut::<ComponentBindings>()
+            .register("card", move |mut cmd| {
+                cmd.insert(HtmlNode(card.clone()));
+            });
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><card><text slot="header">h1</text><text slot="header">h2</text><text slot="body">b1</text><text slot="body">b2</text><text slot="body">b3</text></card></node></template>"#,
+        );
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let card = world
+            .query_filtered::<Entity, (With<HtmlNode>, With<Parent>)>()
+            .single(world);
+
+        let texts = world
+            .get::<Children>(card)
+            .unwrap()
+            .iter()
+            .map(|child| world.get::<Text>(*child).unwrap().0.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(texts, ["h1", "h2", "b1", "b2", "b3"]);
+    }
+
+    #[test]
+    fn test_multiple_slots_fallback_order() {
+        let mut app = test_app();
+        let card = add_template(
+            &mut app,
+            r#"<template><node><slot name="header"><text>d1</text><text>d2</text></slot><slot name="body"><text>d3</text><text>d4</text></slot><text>end</text></node></template>"#,
+        );
+        app.world_mut()
+            .resource_mut::<ComponentBindings>()
+            .register("card", move |mut cmd| {
+                cmd.insert(HtmlNode(card.clone()));
+            });
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><card><text slot="header">h1</text><text slot="header">h2</text></card></node></template>"#,
+        );
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let card = world
+            .query_filtered::<Entity, (With<HtmlNode>, With<Parent>)>()
+            .single(world);
+
+        let texts = world
+            .get::<Children>(card)
+            .unwrap()
+            .iter()
+            .map(|child| world.get::<Text>(*child).unwrap().0.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(texts, ["h1", "h2", "d3", "d4", "end"]);
+    }
+
+    #[test]
+    fn test_shared_atlas_layout() {
+        let mut app = test_app();
+        spawn_template(
+            &mut app,
+            r#"<template><node><image atlas_layout="tile(16,16) cols(4) rows(2)"/><image atlas_layout="tile(16,16) cols(4) rows(2)" atlas_index="3"/><image atlas_layout="tile(8,8) cols(2) rows(2)"/></node></template>"#,
+        );
+        app.update();
+        app.update();
+
+        let world = app.world_mut();
+        let layouts = world
+            .query::<&ImageNode>()
+            .iter(world)
+            .filter_map(|image| image.texture_atlas.as_ref())
+            .map(|atlas| atlas.layout.id())
+            .collect::<Vec<_>>();
+
+        assert_eq!(layouts.len(), 3);
+        assert_eq!(world.resource::<AtlasLayouts>().len(), 2);
+        assert_eq!(world.resource::<Assets<TextureAtlasLayout>>().len(), 2);
+    }
 }
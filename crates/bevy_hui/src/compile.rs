@@ -1,17 +1,14 @@
 use crate::{
+    atlas::UiAtlasAnimation,
     build::{
-        ContentId, HtmlNode, Tags, TemplateExpresions, TemplateProperties,
-        TemplatePropertySubscriber, TemplateScope,
+        find_template, ContentId, HtmlNode, Tags, TemplateExpresions, TemplateProperties,
+        TemplatePropertySubscriber, TemplateScope, UiBranch, UiLoop,
     },
-    data::HtmlTemplate,
-    styles::HtmlStyle,
-};
-use bevy::prelude::*;
-use nom::{
-    bytes::complete::{is_not, tag, take_until},
-    character::complete::multispace0,
-    sequence::{delimited, preceded, tuple},
+    data::{HtmlTemplate, KeyframeAnimation},
+    expr::parse_content,
+    styles::{HtmlStyle, PlayAnimationEvent, StyleTween, UiDisabled},
 };
+use bevy::{ecs::system::SystemParam, prelude::*};
 
 pub struct CompilePlugin;
 impl Plugin for CompilePlugin {
@@ -28,49 +25,75 @@ impl Plugin for CompilePlugin {
 #[derive(Event)]
 pub struct CompileContentEvent;
 
+/// the last animation compiled from a template
+/// expression, recompiles only restart a changed one.
+#[derive(Component, Debug, Deref)]
+pub struct CompiledAnimation(KeyframeAnimation);
+
 fn compile_text(
     trigger: Trigger<CompileContentEvent>,
-    mut nodes: Query<(&ContentId, &TemplateScope, &mut Text)>,
-    root: Query<(&HtmlNode, &TemplateProperties)>,
+    mut nodes: Query<(&ContentId, Option<&mut Text>, Option<&mut TextSpan>)>,
+    scopes: Query<&TemplateScope>,
+    html_nodes: Query<&HtmlNode>,
+    properties: Query<&TemplateProperties>,
     templates: Res<Assets<HtmlTemplate>>,
 ) {
     let entity = trigger.entity();
-    let Ok((content_id, scope, mut text)) = nodes.get_mut(entity) else {
+    let Ok((content_id, text, span)) = nodes.get_mut(entity) else {
         warn!("trying to compile content for {entity}, that does not have any");
         return;
     };
 
-    let Some((template, props)) = root
-        .get(**scope)
-        .ok()
-        .map(|(handle, props)| templates.get(&**handle).map(|d| (d, props)))
-        .flatten()
+    // loop iterations own their properties
+    let props = properties.get(entity).ok().or_else(|| {
+        scopes
+            .get(entity)
+            .ok()
+            .and_then(|scope| properties.get(**scope).ok())
+    });
+
+    let Some((template, props)) = find_template(entity, &scopes, &html_nodes)
+        .and_then(|handle| templates.get(&**handle))
+        .zip(props)
     else {
         warn!("{entity} has no scope!");
         return;
     };
 
-    _ = template
-        .content
-        .get(**content_id)
-        .map(|raw| compile_content(raw.trim(), &props))
-        .map(|compiled| **text = compiled);
+    let Some(raw) = template.content.get(**content_id) else {
+        return;
+    };
+
+    // spans keep their surrounding whitespace
+    match (text, span) {
+        (Some(mut text), _) => **text = compile_content(raw.trim(), props),
+        (_, Some(mut span)) => **span = compile_content(raw, props),
+        _ => (),
+    }
 }
 
 #[derive(Event)]
 pub struct CompileNodeEvent;
+/// the components compiled attributes write to,
+/// besides the node style.
+#[derive(SystemParam)]
+struct CompileTargets<'w, 's> {
+    images: Query<'w, 's, &'static mut ImageNode>,
+    tags: Query<'w, 's, &'static mut Tags>,
+    animations: Query<'w, 's, &'static CompiledAnimation>,
+    server: Res<'w, AssetServer>,
+}
+
 fn compile_node(
     trigger: Trigger<CompileNodeEvent>,
     mut cmd: Commands,
-    mut nodes: Query<(&mut HtmlStyle, &TemplateScope)>,
-    mut images: Query<&mut ImageNode>,
-    mut tags: Query<&mut Tags>,
+    mut nodes: Query<(&mut HtmlStyle, &TemplateScope, Option<&mut StyleTween>)>,
+    mut targets: CompileTargets,
     expressions: Query<&TemplateExpresions>,
     contexts: Query<&TemplateProperties>,
-    server: Res<AssetServer>,
 ) {
     let entity = trigger.entity();
-    let Ok((mut node_style, scope)) = nodes.get_mut(entity) else {
+    let Ok((mut node_style, scope, tween)) = nodes.get_mut(entity) else {
         // unbuild nodes also complain
         // warn!("Trying to compile a non ui node");
         return;
@@ -82,6 +105,13 @@ fn compile_node(
         return;
     };
 
+    // an interrupted tween continues from its current values
+    let previous = tween
+        .as_ref()
+        .and_then(|tween| tween.current(&node_style.computed))
+        .unwrap_or_else(|| node_style.computed.clone());
+    let mut changed = vec![];
+
     if let Ok(expressions) = expressions.get(entity) {
         expressions
             .iter()
@@ -89,24 +119,76 @@ fn compile_node(
                 Some(compiled) => {
                     match compiled {
                         crate::data::Attribute::Style(style_attr) => {
-                            node_style.add_style_attr(style_attr)
+                            let before =
+                                (!style_attr.is_conditional()).then(|| node_style.computed.clone());
+                            node_style.add_style_attr(style_attr.clone());
+
+                            // only tween values, that changed
+                            if before.is_some_and(|before| {
+                                before.reflect_partial_eq(&node_style.computed) != Some(true)
+                            }) {
+                                changed.push(style_attr);
+                            }
                         }
                         crate::data::Attribute::Action(action) => {
                             action.self_insert(cmd.entity(entity))
                         }
                         crate::data::Attribute::Path(path) => {
-                            _ = images.get_mut(entity).map(|mut img| {
-                                img.image = server.load(path);
+                            _ = targets.images.get_mut(entity).map(|mut img| {
+                                img.image = targets.server.load(path);
                             });
                         }
-                        crate::data::Attribute::Tag(key, value) => match tags.get_mut(entity) {
-                            Ok(mut tags) => {
-                                tags.insert(key, value);
+                        crate::data::Attribute::Disabled(disabled) => match disabled {
+                            true => {
+                                cmd.entity(entity).insert(UiDisabled);
                             }
-                            Err(_) => {
-                                warn!("node has to tags")
+                            false => {
+                                cmd.entity(entity).remove::<UiDisabled>();
                             }
                         },
+                        crate::data::Attribute::Animation(animation) => {
+                            let unchanged = targets
+                                .animations
+                                .get(entity)
+                                .is_ok_and(|compiled| **compiled == animation);
+
+                            if !unchanged {
+                                cmd.entity(entity)
+                                    .insert(CompiledAnimation(animation.clone()));
+                                cmd.trigger_targets(PlayAnimationEvent(animation), entity);
+                            }
+                        }
+                        crate::data::Attribute::AtlasIndex(index) => {
+                            _ = targets.images.get_mut(entity).map(|mut img| {
+                                if let Some(atlas) = img.texture_atlas.as_mut() {
+                                    atlas.index = index;
+                                }
+                            });
+                        }
+                        crate::data::Attribute::AtlasFrames(frames) => {
+                            cmd.entity(entity)
+                                .entry::<UiAtlasAnimation>()
+                                .or_default()
+                                .and_modify(move |mut animation| {
+                                    animation.frames = Some(frames);
+                                });
+                        }
+                        crate::data::Attribute::Fps(fps) => {
+                            cmd.entity(entity)
+                                .entry::<UiAtlasAnimation>()
+                                .or_default()
+                                .and_modify(move |mut animation| animation.set_fps(fps));
+                        }
+                        crate::data::Attribute::Tag(key, value) => {
+                            match targets.tags.get_mut(entity) {
+                                Ok(mut tags) => {
+                                    tags.insert(key, value);
+                                }
+                                Err(_) => {
+                                    warn!("node has to tags")
+                                }
+                            }
+                        }
                         rest => {
                             warn!("attribute of this kind cannot be dynamic `{:?}`", rest);
                         }
@@ -119,15 +201,27 @@ fn compile_node(
                 }
             });
     }
+
+    // the first compile happens at spawn and does not tween
+    match tween {
+        Some(mut tween) => tween.start(previous, changed),
+        None => {
+            cmd.entity(entity).try_insert(StyleTween::default());
+        }
+    }
 }
 
 #[derive(Event)]
 pub struct CompileContextEvent;
 
+/// nodes, that build their children from the context
+type ContextExpander = Or<(With<UiLoop>, With<UiBranch>)>;
+
 fn compile_context(
     trigger: Trigger<CompileContextEvent>,
     expressions: Query<(&TemplateExpresions, Option<&TemplateScope>)>,
     text_nodes: Query<(), With<ContentId>>,
+    expanders: Query<(), ContextExpander>,
     subscriber: Query<&TemplatePropertySubscriber>,
     mut properties: Query<&mut TemplateProperties>,
     mut cmd: Commands,
@@ -185,6 +279,9 @@ fn compile_context(
                 cmd.trigger_targets(CompileContextEvent, *sub);
             } else {
                 cmd.trigger_targets(CompileNodeEvent, *sub);
+                if *sub != entity && expanders.get(*sub).is_ok() {
+                    cmd.trigger_targets(CompileContextEvent, *sub);
+                }
             }
             if text_nodes.get(*sub).is_ok() {
                 cmd.trigger_targets(CompileContentEvent, *sub);
@@ -193,29 +290,87 @@ fn compile_context(
     }
 }
 
-// this is bad, only 1 var allowed
 pub(crate) fn compile_content(input: &str, defs: &TemplateProperties) -> String {
-    let mut compiled = String::new();
+    match parse_content(input) {
+        Some(interpolation) => interpolation.eval_lossy(defs),
+        None => input.to_string(),
+    }
+}
 
-    let parts: Result<(&str, (&str, &str)), nom::Err<nom::error::Error<&str>>> = tuple((
-        take_until("{"),
-        delimited(tag("{"), preceded(multispace0, is_not("}")), tag("}")),
-    ))(input);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{spawn_template, styles::UiAnimation, test_app};
 
-    let Ok((input, (literal, key))) = parts else {
-        compiled.push_str(input);
-        return compiled;
-    };
+    #[test]
+    fn test_keep_unchanged_animation() {
+        let mut app = test_app();
+        let root = spawn_template(
+            &mut app,
+            r##"<template>
+                <property name="speed">2s</property>
+                <property name="color">#333</property>
+                <keyframes name="pulse">
+                    from { width: 10px; }
+                    to { width: 20px; }
+                </keyframes>
+                <node>
+                    <node background="{color}" animation="pulse {speed} infinite"/>
+                </node>
+            </template>"##,
+        );
 
-    compiled.push_str(literal);
+        let elapsed = |app: &mut App| {
+            app.world_mut()
+                .query::<&UiAnimation>()
+                .single(app.world())
+                .elapsed
+        };
 
-    if let Some(value) = defs.get(key.trim_end()) {
-        compiled.push_str(value);
-    }
+        let set_prop = |app: &mut App, key: &str, value: &str| {
+            app.world_mut()
+                .get_mut::<TemplateProperties>(root)
+                .unwrap()
+                .insert(key.into(), value.into());
+            app.world_mut().trigger_targets(CompileContextEvent, root);
+            app.update();
+        };
 
-    if input.len() > 0 {
-        compiled.push_str(&compile_content(input, defs));
+        app.update();
+        app.update();
+
+        app.world_mut()
+            .query::<&mut UiAnimation>()
+            .single_mut(app.world_mut())
+            .elapsed = 0.5;
+
+        set_prop(&mut app, "color", "#555");
+        assert!(elapsed(&mut app) >= 0.5);
+
+        set_prop(&mut app, "speed", "3s");
+        assert!(elapsed(&mut app) < 0.5);
     }
 
-    compiled
+    #[test_case::test_case("Press { to open", "Press { to open" ; "unclosed bracket")]
+    #[test_case::test_case("{{name} is {name}", "{name} is bevy" ; "escaped bracket")]
+    #[test_case::test_case("{{ plain", "{ plain" ; "escape only")]
+    fn test_literal_brackets(content: &str, expected: &str) {
+        let mut app = test_app();
+        spawn_template(
+            &mut app,
+            &format!(
+                r#"<template><property name="name">bevy</property><node><text>{content}</text></node></template>"#
+            ),
+        );
+        app.update();
+        app.update();
+
+        let text = app
+            .world_mut()
+            .query::<&Text>()
+            .single(app.world())
+            .0
+            .clone();
+        assert_eq!(text, expected);
+    }
 }
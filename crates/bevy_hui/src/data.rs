@@ -1,26 +1,46 @@
+use crate::expr::{Expr, Interpolation};
 use crate::prelude::*;
 use crate::util::{SlotId, SlotMap};
 use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use bevy::text::FontSmoothing;
 use bevy::ui::widget::NodeImageMode;
 use bevy::utils::HashMap;
 
-#[derive(Debug, Default, Reflect)]
+#[derive(Debug, Default, Clone, Reflect)]
 #[reflect]
 pub enum NodeType {
     #[default]
     Node,
     Image,
     Text,
+    /// inline text of a `<text>` node
+    Span,
     Button,
     Slot,
     Template,
     Property,
+    For,
     Custom(String),
 }
 
+impl NodeType {
+    /// the html tag, used by stylesheet selectors
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            NodeType::Node => Some("node"),
+            NodeType::Image => Some("image"),
+            NodeType::Text => Some("text"),
+            NodeType::Span => Some("span"),
+            NodeType::Button => Some("button"),
+            NodeType::Custom(custom) => Some(custom.as_str()),
+            _ => None,
+        }
+    }
+}
+
 /// a single nodes data
-#[derive(Debug, Default, Reflect)]
+#[derive(Debug, Default, Clone, Reflect)]
 #[reflect]
 pub struct XNode {
     pub uuid: u64,
@@ -34,6 +54,19 @@ pub struct XNode {
     pub tags: HashMap<String, String>,
     pub defs: HashMap<String, String>,
     pub event_listener: Vec<Action>,
+    pub each: Option<ForEach>,
+    pub branch: Option<Branch>,
+    pub bind: Option<String>,
+    pub slot: Option<String>,
+    pub classes: Vec<String>,
+    pub focusable: Option<bool>,
+    pub tab_index: Option<i32>,
+    pub disabled: Option<bool>,
+    pub animation: Option<KeyframeAnimation>,
+    pub atlas_layout: Option<AtlasLayout>,
+    pub atlas_index: Option<usize>,
+    pub atlas_frames: Option<AtlasFrames>,
+    pub fps: Option<f32>,
     pub content_id: SlotId,
     pub node_type: NodeType,
     #[reflect(ignore)]
@@ -49,6 +82,88 @@ pub struct HtmlTemplate {
     pub properties: HashMap<String, String>,
     pub root: Vec<XNode>,
     pub content: SlotMap<String>,
+    /// rules of all inline `<style>` blocks
+    pub style: HtmlStyleSheet,
+    /// paths of `<style src=".."/>` stylesheets
+    pub style_paths: Vec<String>,
+    /// the loaded `<style src=".."/>` stylesheets
+    pub stylesheets: Vec<Handle<HtmlStyleSheet>>,
+    /// all `<keyframes name="..">` by name
+    pub keyframes: HashMap<String, Keyframes>,
+}
+
+impl HtmlTemplate {
+    /// styles of all rules matching the node, ordered by
+    /// specificity. External sheets come before inline `<style>`.
+    pub fn matching_styles(&self, node: &XNode, sheets: &Assets<HtmlStyleSheet>) -> Vec<StyleAttr> {
+        let mut rules = self
+            .stylesheets
+            .iter()
+            .filter_map(|handle| sheets.get(handle))
+            .chain(std::iter::once(&self.style))
+            .flat_map(|sheet| sheet.rules.iter())
+            .filter(|rule| rule.selector.matches(node))
+            .collect::<Vec<_>>();
+
+        // stable, keeps the source order for equal specificity
+        rules.sort_by_key(|rule| rule.selector.specificity());
+        rules
+            .into_iter()
+            .flat_map(|rule| rule.styles.iter().cloned())
+            .collect()
+    }
+}
+
+/// a list of style rules, from an inline `<style>`
+/// block or a `.hss` stylesheet asset.
+#[derive(Debug, Default, Clone, Asset, Reflect)]
+#[reflect]
+pub struct HtmlStyleSheet {
+    pub rules: Vec<StyleRule>,
+}
+
+/// `.card, button:hover { background: #333; }`
+#[derive(Debug, Clone, Reflect)]
+#[reflect]
+pub struct StyleRule {
+    pub selector: Selector,
+    pub styles: Vec<StyleAttr>,
+}
+
+/// matches nodes by tag, classes and id
+///
+/// `button`, `.card`, `#title`, `button.primary.large`
+#[derive(Debug, Clone, Default, PartialEq, Reflect)]
+#[reflect]
+pub struct Selector {
+    pub tag: Option<String>,
+    pub classes: Vec<String>,
+    pub id: Option<String>,
+}
+
+impl Selector {
+    pub fn matches(&self, node: &XNode) -> bool {
+        self.tag
+            .as_ref()
+            .is_none_or(|tag| node.node_type.tag() == Some(tag.as_str()))
+            && self
+                .id
+                .as_ref()
+                .is_none_or(|id| node.id.as_ref() == Some(id))
+            && self
+                .classes
+                .iter()
+                .all(|class| node.classes.contains(class))
+    }
+
+    /// id, then classes, then tag. Like css.
+    pub fn specificity(&self) -> (usize, usize, usize) {
+        (
+            self.id.iter().count(),
+            self.classes.len(),
+            self.tag.iter().count(),
+        )
+    }
 }
 
 /// any valid attribute that can be found
@@ -66,22 +181,309 @@ pub enum Attribute {
     Id(String),
     Watch(String),
     Tag(String, String),
+    Each(ForEach),
+    Branch(Branch),
+    Bind(String),
+    Slot(String),
+    Class(Vec<String>),
+    Focusable(bool),
+    TabIndex(i32),
+    Disabled(bool),
+    Animation(KeyframeAnimation),
+    AtlasLayout(AtlasLayout),
+    AtlasIndex(usize),
+    AtlasFrames(AtlasFrames),
+    Fps(f32),
+}
+
+/// loop definition of a `<for>` node
+///
+/// `each="item in {items}"` repeats the children once
+/// per comma separated value found in the property `items`.
+#[derive(Debug, Reflect, PartialEq, Clone)]
+#[reflect]
+pub struct ForEach {
+    /// name of the property holding the current value
+    pub binding: String,
+    /// the property key of the list
+    pub source: String,
+}
+
+/// conditional rendering of a node
+///
+/// a chain starts with `if="{..}"`, followed by any
+/// number of `else_if="{..}"` and an optional `<else>`
+/// sibling. The first branch that holds is spawned.
+#[derive(Debug, Reflect, PartialEq, Clone)]
+#[reflect]
+pub enum Branch {
+    If(Expr),
+    ElseIf(Expr),
+    Else,
+}
+
+/// stops of a `<keyframes name="pulse">` block,
+/// sorted by position.
+///
+/// `0% { background: #333; } 50% { background: #555; }`
+#[derive(Debug, Default, Clone, Reflect)]
+#[reflect]
+pub struct Keyframes {
+    pub stops: Vec<Keyframe>,
+}
+
+/// a single stop `50% { width: 10px; }`
+#[derive(Debug, Clone, Reflect)]
+#[reflect]
+pub struct Keyframe {
+    /// position in the animation, `0..=1`
+    pub at: f32,
+    pub styles: Vec<StyleAttr>,
+}
+
+impl Keyframe {
+    fn find(&self, kind: std::mem::Discriminant<StyleAttr>) -> Option<(f32, &StyleAttr)> {
+        self.styles
+            .iter()
+            .find(|style| std::mem::discriminant(*style) == kind)
+            .map(|style| (self.at, style))
+    }
+}
+
+impl Keyframes {
+    /// each animated property at `t` as `(from, to, ratio)`.
+    ///
+    /// Properties interpolate between the closest stops
+    /// defining them. `from` is `None` before the first stop,
+    /// the node style is the start value then.
+    pub fn sample(&self, t: f32) -> Vec<(Option<&StyleAttr>, &StyleAttr, f32)> {
+        let mut kinds = vec![];
+        for style in self.stops.iter().flat_map(|stop| stop.styles.iter()) {
+            let kind = std::mem::discriminant(style);
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+
+        kinds
+            .into_iter()
+            .filter_map(|kind| {
+                let prev = self
+                    .stops
+                    .iter()
+                    .rev()
+                    .filter(|stop| stop.at <= t)
+                    .find_map(|stop| stop.find(kind));
+                let next = self
+                    .stops
+                    .iter()
+                    .filter(|stop| stop.at > t)
+                    .find_map(|stop| stop.find(kind));
+
+                match (prev, next) {
+                    (Some((from_at, from)), Some((to_at, to))) => {
+                        Some((Some(from), to, (t - from_at) / (to_at - from_at)))
+                    }
+                    (None, Some((to_at, to))) => Some((None, to, t / to_at)),
+                    (Some((_, from)), None) => Some((None, from, 1.)),
+                    (None, None) => None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// `animation="pulse 1.2s infinite alternate sine_in_out"`
+///
+/// plays the keyframes `pulse` of the template.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect]
+pub struct KeyframeAnimation {
+    /// name of the keyframes
+    pub name: String,
+    /// duration of one iteration in seconds
+    pub duration: f32,
+    pub repeat: AnimationRepeat,
+    /// every second iteration plays backwards
+    pub alternate: bool,
+    /// easing between two stops
+    pub easing: Option<EaseFunction>,
+}
+
+impl KeyframeAnimation {
+    pub fn new(name: impl Into<String>, duration: f32) -> Self {
+        Self {
+            name: name.into(),
+            duration,
+            repeat: AnimationRepeat::Count(1),
+            alternate: false,
+            easing: None,
+        }
+    }
+
+    pub fn infinite(mut self) -> Self {
+        self.repeat = AnimationRepeat::Infinite;
+        self
+    }
+
+    pub fn repeat(mut self, count: u32) -> Self {
+        self.repeat = AnimationRepeat::Count(count);
+        self
+    }
+
+    pub fn alternate(mut self) -> Self {
+        self.alternate = true;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: EaseFunction) -> Self {
+        self.easing = Some(easing);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect]
+pub enum AnimationRepeat {
+    Count(u32),
+    Infinite,
+}
+
+/// timing of a single property
+///
+/// `transition="background 100ms linear, width 200ms/400ms cubic_out"`
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect]
+pub struct StyleTransition {
+    /// name of the style attribute
+    pub property: String,
+    /// seconds entering a state
+    pub duration: f32,
+    /// seconds leaving a state, `duration` if not set
+    pub duration_out: Option<f32>,
+    /// the node `ease` if not set
+    pub easing: Option<EaseFunction>,
+}
+
+/// grid of a texture atlas on an image
+///
+/// `atlas_layout="tile(32,32) cols(8) rows(4) padding(2,2) offset(0,0)"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect]
+pub struct AtlasLayout {
+    pub tile_size: UVec2,
+    pub columns: u32,
+    pub rows: u32,
+    pub padding: Option<UVec2>,
+    pub offset: Option<UVec2>,
+}
+
+impl Default for AtlasLayout {
+    fn default() -> Self {
+        Self {
+            tile_size: UVec2::ZERO,
+            columns: 1,
+            rows: 1,
+            padding: None,
+            offset: None,
+        }
+    }
+}
+
+impl AtlasLayout {
+    pub fn build(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(
+            self.tile_size,
+            self.columns,
+            self.rows,
+            self.padding,
+            self.offset,
+        )
+    }
+}
+
+/// range of played atlas frames, both ends included
+///
+/// `atlas_frames="0..7"`
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect]
+pub struct AtlasFrames {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl AtlasFrames {
+    /// the frame `steps` after `index`, wraps around
+    /// and starts at `first` if outside of the range.
+    pub fn advance(&self, index: usize, steps: usize) -> usize {
+        if index < self.first || index > self.last {
+            return self.first;
+        }
+
+        let len = self.last - self.first + 1;
+        self.first + (index - self.first + steps) % len
+    }
+}
+
+/// an offset copy of the text, drawn behind it
+///
+/// `text_shadow="2px 2px #000A"`
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect]
+pub struct TextShadow {
+    pub x_offset: Val,
+    pub y_offset: Val,
+    pub color: Color,
+}
+
+/// condition of a media prefix, `@w<800:` or
+/// a named breakpoint `@sm:`
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect]
+pub enum MediaQuery {
+    /// looked up in `HuiBreakpoints`
+    Breakpoint(String),
+    Width(MediaCompare, f32),
+    Height(MediaCompare, f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect]
+pub enum MediaCompare {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl MediaCompare {
+    pub fn eval(&self, value: f32, limit: f32) -> bool {
+        match self {
+            MediaCompare::Less => value < limit,
+            MediaCompare::LessEqual => value <= limit,
+            MediaCompare::Greater => value > limit,
+            MediaCompare::GreaterEqual => value >= limit,
+        }
+    }
 }
 
 /// raw attribute
 #[derive(Debug, Reflect, PartialEq, Clone)]
 #[reflect]
 pub struct AttrTokens {
+    /// attribute prefix like `hover` in `hover:width`
     pub prefix: Option<String>,
+    /// attribute name
     pub ident: String,
-    pub key: String,
+    /// unparsed attribute value, `icons/{name}.png`
+    pub raw: String,
+    /// parsed attribute value
+    pub value: Interpolation,
 }
 
 impl AttrTokens {
     pub fn compile(&self, props: &TemplateProperties) -> Option<Attribute> {
-        let Some(prop_val) = props.get(&self.key) else {
-            return None;
-        };
+        let prop_val = self.value.eval(props)?;
 
         let (_, attr) = match crate::parse::attribute_from_parts::<nom::error::VerboseError<&[u8]>>(
             self.prefix.as_ref().map(|s| s.as_bytes()),
@@ -107,11 +509,30 @@ impl AttrTokens {
 #[derive(Debug, Reflect, PartialEq, Clone)]
 #[reflect]
 pub enum Action {
-    OnPress(Vec<String>),
-    OnEnter(Vec<String>),
-    OnExit(Vec<String>),
-    OnSpawn(Vec<String>),
-    OnChange(Vec<String>),
+    OnPress(Vec<FnCall>),
+    OnEnter(Vec<FnCall>),
+    OnExit(Vec<FnCall>),
+    OnSpawn(Vec<FnCall>),
+    OnChange(Vec<FnCall>),
+    OnRelease(Vec<FnCall>),
+    OnClick(Vec<FnCall>),
+    OnDoubleClick(Vec<FnCall>),
+    OnRightClick(Vec<FnCall>),
+    OnDragStart(Vec<FnCall>),
+    OnDrag(Vec<FnCall>),
+    OnDragEnd(Vec<FnCall>),
+    OnScroll(Vec<FnCall>),
+    OnFocus(Vec<FnCall>),
+    OnBlur(Vec<FnCall>),
+}
+
+/// A function binding call, `buy_item(sword, 3)`.
+/// Arguments are passed as raw strings, see [crate::prelude::HuiArgs].
+#[derive(Debug, Reflect, PartialEq, Clone, Default)]
+#[reflect]
+pub struct FnCall {
+    pub name: String,
+    pub args: Vec<String>,
 }
 
 impl Action {
@@ -132,6 +553,134 @@ impl Action {
             Action::OnChange(fn_id) => {
                 cmd.insert(crate::prelude::OnUiChange(fn_id));
             }
+            Action::OnRelease(fn_id) => {
+                cmd.insert(crate::prelude::OnUiRelease(fn_id));
+            }
+            Action::OnClick(fn_id) => {
+                cmd.insert(crate::prelude::OnUiClick(fn_id));
+            }
+            Action::OnDoubleClick(fn_id) => {
+                cmd.insert(crate::prelude::OnUiDoubleClick(fn_id));
+            }
+            Action::OnRightClick(fn_id) => {
+                cmd.insert(crate::prelude::OnUiRightClick(fn_id));
+            }
+            Action::OnDragStart(fn_id) => {
+                cmd.insert(crate::prelude::OnUiDragStart(fn_id));
+            }
+            Action::OnDrag(fn_id) => {
+                cmd.insert(crate::prelude::OnUiDrag(fn_id));
+            }
+            Action::OnDragEnd(fn_id) => {
+                cmd.insert(crate::prelude::OnUiDragEnd(fn_id));
+            }
+            Action::OnScroll(fn_id) => {
+                cmd.insert(crate::prelude::OnUiScroll(fn_id));
+            }
+            Action::OnFocus(fn_id) => {
+                cmd.insert(crate::prelude::OnUiFocus(fn_id));
+            }
+            Action::OnBlur(fn_id) => {
+                cmd.insert(crate::prelude::OnUiBlur(fn_id));
+            }
+        }
+    }
+}
+
+impl StyleAttr {
+    /// true for styles only active in a state `hover:..`
+    pub fn is_conditional(&self) -> bool {
+        matches!(
+            self,
+            StyleAttr::Hover(_)
+                | StyleAttr::Pressed(_)
+                | StyleAttr::Active(_)
+                | StyleAttr::Focus(_)
+                | StyleAttr::Disabled(_)
+                | StyleAttr::Enter(_)
+                | StyleAttr::Exit(_)
+                | StyleAttr::State(_, _)
+                | StyleAttr::Media(_, _)
+        )
+    }
+
+    /// the attribute name, without any prefix
+    pub fn name(&self) -> &'static str {
+        match self {
+            StyleAttr::Display(..) => "display",
+            StyleAttr::Position(..) => "position",
+            StyleAttr::Overflow(..) => "overflow",
+            StyleAttr::OverflowClipMargin(..) => "overflow_clip_margin",
+            StyleAttr::Left(..) => "left",
+            StyleAttr::Right(..) => "right",
+            StyleAttr::Top(..) => "top",
+            StyleAttr::Bottom(..) => "bottom",
+            StyleAttr::Width(..) => "width",
+            StyleAttr::Height(..) => "height",
+            StyleAttr::MinWidth(..) => "min_width",
+            StyleAttr::MinHeight(..) => "min_height",
+            StyleAttr::MaxWidth(..) => "max_width",
+            StyleAttr::MaxHeight(..) => "max_height",
+            StyleAttr::AspectRatio(..) => "aspect_ratio",
+            StyleAttr::AlignItems(..) => "align_items",
+            StyleAttr::JustifyItems(..) => "justify_items",
+            StyleAttr::AlignSelf(..) => "align_self",
+            StyleAttr::JustifySelf(..) => "justify_self",
+            StyleAttr::AlignContent(..) => "align_content",
+            StyleAttr::JustifyContent(..) => "justify_content",
+            StyleAttr::Margin(..) => "margin",
+            StyleAttr::Padding(..) => "padding",
+            StyleAttr::Border(..) => "border",
+            StyleAttr::BorderColor(..) => "border_color",
+            StyleAttr::BorderRadius(..) => "border_radius",
+            StyleAttr::Outline(..) => "outline",
+            StyleAttr::FlexDirection(..) => "flex_direction",
+            StyleAttr::FlexWrap(..) => "flex_wrap",
+            StyleAttr::FlexGrow(..) => "flex_grow",
+            StyleAttr::FlexShrink(..) => "flex_shrink",
+            StyleAttr::FlexBasis(..) => "flex_basis",
+            StyleAttr::RowGap(..) => "row_gap",
+            StyleAttr::ColumnGap(..) => "column_gap",
+            StyleAttr::GridAutoFlow(..) => "grid_auto_flow",
+            StyleAttr::GridTemplateRows(..) => "grid_template_rows",
+            StyleAttr::GridTemplateColumns(..) => "grid_template_columns",
+            StyleAttr::GridAutoRows(..) => "grid_auto_rows",
+            StyleAttr::GridAutoColumns(..) => "grid_auto_columns",
+            StyleAttr::GridRow(..) => "grid_row",
+            StyleAttr::GridColumn(..) => "grid_column",
+            StyleAttr::ZIndex(..) => "z_index",
+            StyleAttr::GlobalZIndex(..) => "global_z_index",
+            StyleAttr::Visibility(..) => "visibility",
+            StyleAttr::FontSize(..) => "font_size",
+            StyleAttr::Font(..) => "font",
+            StyleAttr::FontColor(..) => "font_color",
+            StyleAttr::TextJustify(..) => "text_justify",
+            StyleAttr::LineBreak(..) => "line_break",
+            StyleAttr::FontSmoothing(..) => "font_smoothing",
+            StyleAttr::TextShadow(..) => "text_shadow",
+            StyleAttr::Background(..) => "background",
+            StyleAttr::ShadowColor(..) => "shadow_color",
+            StyleAttr::ShadowOffset(..) => "shadow_offset",
+            StyleAttr::ShadowSpread(..) => "shadow_spread",
+            StyleAttr::ShadowBlur(..) => "shadow_blur",
+            StyleAttr::Delay(..) => "delay",
+            StyleAttr::DelayOut(..) => "delay_out",
+            StyleAttr::Easing(..) => "ease",
+            StyleAttr::Transition(..) => "transition",
+            StyleAttr::ImageScaleMode(..) => "image_mode",
+            StyleAttr::ImageRegion(..) => "image_region",
+            StyleAttr::ImageColor(..) => "image_color",
+            StyleAttr::FlipX(..) => "flip_x",
+            StyleAttr::FlipY(..) => "flip_y",
+            StyleAttr::Hover(style)
+            | StyleAttr::Pressed(style)
+            | StyleAttr::Active(style)
+            | StyleAttr::Focus(style)
+            | StyleAttr::Disabled(style)
+            | StyleAttr::Enter(style)
+            | StyleAttr::Exit(style)
+            | StyleAttr::State(_, style)
+            | StyleAttr::Media(_, style) => style.name(),
         }
     }
 }
@@ -190,11 +739,21 @@ pub enum StyleAttr {
     GridRow(GridPlacement),
     GridColumn(GridPlacement),
 
+    // -----
+    // layering
+    ZIndex(i32),
+    GlobalZIndex(i32),
+    Visibility(Visibility),
+
     // -----
     // font
     FontSize(f32),
     Font(String),
     FontColor(Color),
+    TextJustify(JustifyText),
+    LineBreak(LineBreak),
+    FontSmoothing(FontSmoothing),
+    TextShadow(TextShadow),
 
     // -----
     // color
@@ -208,16 +767,34 @@ pub enum StyleAttr {
     Hover(#[reflect(ignore)] Box<StyleAttr>),
     Pressed(#[reflect(ignore)] Box<StyleAttr>),
     Active(#[reflect(ignore)] Box<StyleAttr>),
+    Focus(#[reflect(ignore)] Box<StyleAttr>),
+    Disabled(#[reflect(ignore)] Box<StyleAttr>),
+    /// start values after spawning
+    Enter(#[reflect(ignore)] Box<StyleAttr>),
+    /// end values of `despawn_animated`
+    Exit(#[reflect(ignore)] Box<StyleAttr>),
+    /// custom state `state(checked):background="#FFF"`,
+    /// active while the state is in `UiStates`
+    State(String, #[reflect(ignore)] Box<StyleAttr>),
+    /// active while the viewport matches `@w<800:width="100%"`
+    Media(MediaQuery, #[reflect(ignore)] Box<StyleAttr>),
 
     // -----
     // animations
     Delay(f32),
+    /// duration leaving a state, `delay` if not set
+    DelayOut(f32),
     Easing(EaseFunction),
+    Transition(Vec<StyleTransition>),
 
     // -----
     // image
     ImageScaleMode(NodeImageMode),
     ImageRegion(Rect),
+    /// tint of the image
+    ImageColor(Color),
+    FlipX(bool),
+    FlipY(bool),
 }
 
 impl Default for StyleAttr {
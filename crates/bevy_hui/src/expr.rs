@@ -0,0 +1,568 @@
+use crate::build::TemplateProperties;
+use bevy::reflect::Reflect;
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_until, take_while, take_while1},
+    character::complete::multispace0,
+    combinator::{all_consuming, cut, map, not, opt, recognize, value},
+    error::{context, ContextError, ParseError},
+    multi::many0,
+    number::complete::float,
+    sequence::{delimited, preceded, terminated, tuple},
+    IResult,
+};
+
+/// a parsed placeholder expression
+///
+/// `{hp * 2}`, `{'Level ' + level}`, `{selected ? primary : secondary}`
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(opaque)]
+pub enum Expr {
+    Literal(Value),
+    Key(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// `key ?? fallback`
+    Default(Box<Expr>, Box<Expr>),
+}
+
+impl Default for Expr {
+    fn default() -> Self {
+        Expr::Literal(Value::Text(String::new()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+}
+
+/// result of an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f32),
+    Text(String),
+    Bool(bool),
+}
+
+impl Value {
+    /// numbers and numeric text
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Text(s) => s.trim().parse::<f32>().ok(),
+            Value::Bool(_) => None,
+        }
+    }
+
+    /// `false`, `0` and empty text are false
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.,
+            Value::Text(s) => !matches!(s.trim(), "" | "false" | "0"),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(n) if n.fract() == 0. && n.abs() < 1e9 => write!(f, "{}", *n as i64),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::Text(s) => write!(f, "{s}"),
+            Value::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl Expr {
+    /// all property keys used by this expression
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            Expr::Literal(_) => vec![],
+            Expr::Key(key) => vec![key.as_str()],
+            Expr::Not(expr) | Expr::Neg(expr) => expr.keys(),
+            Expr::Binary(_, a, b) | Expr::Default(a, b) => [a.keys(), b.keys()].concat(),
+            Expr::Ternary(cond, a, b) => [cond.keys(), a.keys(), b.keys()].concat(),
+        }
+    }
+
+    /// evaluates against the template properties.
+    /// `None` if a required property is missing, or
+    /// arithmetic has no numeric result.
+    pub fn eval(&self, props: &TemplateProperties) -> Option<Value> {
+        match self {
+            Expr::Literal(value) => Some(value.clone()),
+            Expr::Key(key) => props.get(key).map(|v| Value::Text(v.clone())),
+            Expr::Not(expr) => Some(Value::Bool(!expr.eval(props)?.is_truthy())),
+            Expr::Neg(expr) => Some(Value::Number(-expr.eval(props)?.as_number()?)),
+            Expr::Ternary(cond, a, b) => match cond.eval(props)?.is_truthy() {
+                true => a.eval(props),
+                false => b.eval(props),
+            },
+            Expr::Default(expr, fallback) => expr.eval(props).or_else(|| fallback.eval(props)),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval(props)?, b.eval(props)?);
+                binary(*op, a, b)
+            }
+        }
+    }
+}
+
+fn binary(op: BinaryOp, a: Value, b: Value) -> Option<Value> {
+    let numbers = a.as_number().zip(b.as_number());
+
+    // text operands and division by zero fail
+    let arithmetic = |calc: fn(f32, f32) -> f32| {
+        numbers
+            .map(|(a, b)| calc(a, b))
+            .filter(|n| n.is_finite())
+            .map(Value::Number)
+    };
+
+    Some(match op {
+        BinaryOp::Add => match numbers {
+            Some(_) => return arithmetic(|a, b| a + b),
+            None => Value::Text(format!("{a}{b}")),
+        },
+        BinaryOp::Sub => return arithmetic(|a, b| a - b),
+        BinaryOp::Mul => return arithmetic(|a, b| a * b),
+        BinaryOp::Div => return arithmetic(|a, b| a / b),
+        BinaryOp::Rem => return arithmetic(|a, b| a % b),
+        BinaryOp::Eq => Value::Bool(equals(&a, &b, numbers)),
+        BinaryOp::NotEq => Value::Bool(!equals(&a, &b, numbers)),
+        BinaryOp::Less => Value::Bool(numbers.is_some_and(|(a, b)| a < b)),
+        BinaryOp::LessEq => Value::Bool(numbers.is_some_and(|(a, b)| a <= b)),
+        BinaryOp::Greater => Value::Bool(numbers.is_some_and(|(a, b)| a > b)),
+        BinaryOp::GreaterEq => Value::Bool(numbers.is_some_and(|(a, b)| a >= b)),
+        BinaryOp::And => Value::Bool(a.is_truthy() && b.is_truthy()),
+        BinaryOp::Or => Value::Bool(a.is_truthy() || b.is_truthy()),
+    })
+}
+
+fn equals(a: &Value, b: &Value, numbers: Option<(f32, f32)>) -> bool {
+    match (numbers, a, b) {
+        (Some((a, b)), _, _) => a == b,
+        (None, Value::Bool(a), b) | (None, b, Value::Bool(a)) => *a == b.is_truthy(),
+        (None, a, b) => a.to_string().trim() == b.to_string().trim(),
+    }
+}
+
+/// text mixed with placeholders
+///
+/// `icons/{name}.png`, `{x}px {y}px`
+#[derive(Debug, Clone, PartialEq, Default, Reflect)]
+#[reflect(opaque)]
+pub struct Interpolation(pub Vec<Segment>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Literal(String),
+    Expr(Expr),
+}
+
+impl Interpolation {
+    /// all property keys used by the placeholders
+    pub fn keys(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .flat_map(|segment| match segment {
+                Segment::Literal(_) => vec![],
+                Segment::Expr(expr) => expr.keys(),
+            })
+            .collect()
+    }
+
+    /// false for plain text and escaped brackets
+    pub fn has_placeholders(&self) -> bool {
+        self.0
+            .iter()
+            .any(|segment| matches!(segment, Segment::Expr(_)))
+    }
+
+    /// `None` if any placeholder fails.
+    pub fn eval(&self, props: &TemplateProperties) -> Option<String> {
+        self.0.iter().try_fold(String::new(), |mut out, segment| {
+            match segment {
+                Segment::Literal(literal) => out.push_str(literal),
+                Segment::Expr(expr) => out.push_str(&expr.eval(props)?.to_string()),
+            }
+            Some(out)
+        })
+    }
+
+    /// failing placeholders are left empty.
+    pub fn eval_lossy(&self, props: &TemplateProperties) -> String {
+        self.0.iter().fold(String::new(), |mut out, segment| {
+            match segment {
+                Segment::Literal(literal) => out.push_str(literal),
+                Segment::Expr(expr) => {
+                    if let Some(value) = expr.eval(props) {
+                        out.push_str(&value.to_string());
+                    }
+                }
+            }
+            out
+        })
+    }
+}
+
+/// parses text content at runtime, content is
+/// validated when loading the template.
+pub(crate) fn parse_content(input: &str) -> Option<Interpolation> {
+    let parsed: Result<_, nom::Err<nom::error::Error<&[u8]>>> =
+        all_consuming(parse_interpolation)(input.as_bytes());
+    parsed.ok().map(|(_, interpolation)| interpolation)
+}
+
+/// parses text with any number of placeholders. `{{` is a
+/// literal bracket, so is a `{` without a closing `}`.
+pub(crate) fn parse_interpolation<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Interpolation, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    map(
+        many0(alt((
+            map(is_not("{"), |s| {
+                Segment::Literal(String::from_utf8_lossy(s).to_string())
+            }),
+            value(Segment::Literal("{".into()), tag("{{")),
+            parse_unclosed,
+            map(parse_placeholder, Segment::Expr),
+        ))),
+        Interpolation,
+    )(input)
+}
+
+// `Press { to open`, the rest is text
+fn parse_unclosed<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Segment, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (rest, _) = tag("{")(input)?;
+    if rest.contains(&b'}') {
+        return Err(nom::Err::Error(E::from_error_kind(
+            input,
+            nom::error::ErrorKind::Tag,
+        )));
+    }
+
+    let literal = Segment::Literal(String::from_utf8_lossy(input).to_string());
+    Ok((&input[input.len()..], literal))
+}
+
+/// parses a full expression, surrounding whitespace is ignored.
+pub(crate) fn parse_expr<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "Is not a valid expression, try `{key}`, `{a + b}` or `{cond ? a : b}`",
+        delimited(multispace0, parse_ternary, multispace0),
+    )(input)
+}
+
+/// `{expr}`, fails hard once the opening bracket is found.
+pub(crate) fn parse_placeholder<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    preceded(tag("{"), cut(terminated(parse_expr, tag("}"))))(input)
+}
+
+fn parse_ternary<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, cond) = parse_default(input)?;
+    let (input, branches) = opt(tuple((
+        ws(tag("?")),
+        parse_ternary,
+        ws(tag(":")),
+        parse_ternary,
+    )))(input)?;
+
+    Ok((
+        input,
+        match branches {
+            Some((_, a, _, b)) => Expr::Ternary(Box::new(cond), Box::new(a), Box::new(b)),
+            None => cond,
+        },
+    ))
+}
+
+fn parse_default<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, first) = parse_or(input)?;
+    let (input, rest) = many0(preceded(ws(tag("??")), parse_or))(input)?;
+    Ok((
+        input,
+        rest.into_iter().fold(first, |acc, next| {
+            Expr::Default(Box::new(acc), Box::new(next))
+        }),
+    ))
+}
+
+fn parse_or<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    binary_chain(input, parse_and, |i| value(BinaryOp::Or, tag("||"))(i))
+}
+
+fn parse_and<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    binary_chain(input, parse_equality, |i| {
+        value(BinaryOp::And, tag("&&"))(i)
+    })
+}
+
+fn parse_equality<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    binary_chain(input, parse_comparison, |i| {
+        alt((
+            value(BinaryOp::Eq, tag("==")),
+            value(BinaryOp::NotEq, tag("!=")),
+        ))(i)
+    })
+}
+
+fn parse_comparison<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    binary_chain(input, parse_additive, |i| {
+        alt((
+            value(BinaryOp::LessEq, tag("<=")),
+            value(BinaryOp::GreaterEq, tag(">=")),
+            value(BinaryOp::Less, tag("<")),
+            value(BinaryOp::Greater, tag(">")),
+        ))(i)
+    })
+}
+
+fn parse_additive<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    binary_chain(input, parse_multiplicative, |i| {
+        alt((
+            value(BinaryOp::Add, tag("+")),
+            value(BinaryOp::Sub, tag("-")),
+        ))(i)
+    })
+}
+
+fn parse_multiplicative<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    binary_chain(input, parse_unary, |i| {
+        alt((
+            value(BinaryOp::Mul, tag("*")),
+            value(BinaryOp::Div, tag("/")),
+            value(BinaryOp::Rem, tag("%")),
+        ))(i)
+    })
+}
+
+fn parse_unary<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    alt((
+        map(
+            preceded(ws(terminated(tag("!"), not(tag("=")))), parse_unary),
+            |e| Expr::Not(Box::new(e)),
+        ),
+        map(preceded(ws(tag("-")), parse_unary), |e| {
+            Expr::Neg(Box::new(e))
+        }),
+        parse_primary,
+    ))(input)
+}
+
+fn parse_primary<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    ws(alt((
+        delimited(tag("("), parse_ternary, ws(tag(")"))),
+        map(parse_string, |s| Expr::Literal(Value::Text(s))),
+        map(parse_ident, |ident| match ident.as_str() {
+            "true" => Expr::Literal(Value::Bool(true)),
+            "false" => Expr::Literal(Value::Bool(false)),
+            _ => Expr::Key(ident),
+        }),
+        map(float, |n| Expr::Literal(Value::Number(n))),
+    )))(input)
+}
+
+fn parse_string<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], String, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    map(
+        alt((
+            delimited(tag("'"), take_until("'"), tag("'")),
+            delimited(tag("\""), take_until("\""), tag("\"")),
+        )),
+        |s| String::from_utf8_lossy(s).to_string(),
+    )(input)
+}
+
+/// `[a-zA-Z_][a-zA-Z0-9_.]*`, optionally prefixed
+/// with a reflected source `res:` or `comp:`
+pub(crate) fn parse_ident<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], String, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    map(
+        recognize(tuple((
+            opt(alt((tag("res:"), tag("comp:")))),
+            take_while1(|b: u8| b.is_ascii_alphabetic() || b == b'_'),
+            take_while(|b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'.'),
+        ))),
+        |s| String::from_utf8_lossy(s).to_string(),
+    )(input)
+}
+
+fn binary_chain<'a, E, P, O>(
+    input: &'a [u8],
+    mut operand: P,
+    mut operator: O,
+) -> IResult<&'a [u8], Expr, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+    P: FnMut(&'a [u8]) -> IResult<&'a [u8], Expr, E>,
+    O: FnMut(&'a [u8]) -> IResult<&'a [u8], BinaryOp, E>,
+{
+    let (mut input, mut expr) = operand(input)?;
+    loop {
+        let Ok((rest, op)) = ws(&mut operator)(input) else {
+            return Ok((input, expr));
+        };
+        let (rest, rhs) = operand(rest)?;
+        expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        input = rest;
+    }
+}
+
+fn ws<'a, E, O, P>(parser: P) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], O, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+    P: FnMut(&'a [u8]) -> IResult<&'a [u8], O, E>,
+{
+    delimited(multispace0, parser, multispace0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::error::VerboseError;
+    use test_case::test_case;
+
+    fn props() -> TemplateProperties {
+        TemplateProperties::default()
+            .with("hp", "42")
+            .with("name", "bevy")
+            .with("selected", "true")
+            .with("primary", "#FFF")
+            .with("secondary", "#000")
+            .with("empty", "")
+            .with("res:Stats.gold", "10")
+            .with("comp:Health.current", "7")
+    }
+
+    #[test_case("hp", "42")]
+    #[test_case("hp * 2 + 1", "85")]
+    #[test_case("(hp - 2) / 8", "5")]
+    #[test_case("hp % 5", "2")]
+    #[test_case("-hp", "-42" ; "negation")]
+    #[test_case("'Hello ' + name", "Hello bevy")]
+    #[test_case("hp + '%'", "42%" ; "number and text")]
+    #[test_case("hp >= 42 && name == 'bevy'", "true")]
+    #[test_case("hp < 10 || !selected", "false")]
+    #[test_case("selected ? primary : secondary", "#FFF")]
+    #[test_case("empty ? primary : secondary", "#000")]
+    #[test_case("missing ?? 'fallback'", "fallback")]
+    #[test_case("hp ?? 0", "42")]
+    #[test_case("hp > 10 ? 'high' : hp > 5 ? 'mid' : 'low'", "high")]
+    #[test_case("res:Stats.gold * 2", "20")]
+    #[test_case("selected ? comp:Health.current : 0", "7")]
+    fn test_eval(input: &str, expected: &str) {
+        let (_, expr) = all_consuming(parse_expr::<VerboseError<_>>)(input.as_bytes()).unwrap();
+        let result = expr.eval(&props()).unwrap();
+        assert_eq!(result.to_string(), expected);
+    }
+
+    #[test_case("icons/{name}.png", "icons/bevy.png")]
+    #[test_case("{hp}px {hp / 2}px", "42px 21px")]
+    #[test_case("no placeholder", "no placeholder")]
+    #[test_case("Press { to open", "Press { to open" ; "unclosed bracket")]
+    #[test_case("{{hp} is {hp}", "{hp} is 42" ; "escaped bracket")]
+    #[test_case("{{ {name} }", "{ bevy }" ; "escape before placeholder")]
+    fn test_interpolation(input: &str, expected: &str) {
+        let (_, value) =
+            all_consuming(parse_interpolation::<VerboseError<_>>)(input.as_bytes()).unwrap();
+        assert_eq!(value.eval(&props()).unwrap(), expected);
+    }
+
+    #[test_case("{hp}", true)]
+    #[test_case("{{hp}", false ; "escaped")]
+    #[test_case("Press { to open", false ; "unclosed")]
+    #[test_case("plain", false)]
+    fn test_has_placeholders(input: &str, expected: bool) {
+        let content = parse_content(input).unwrap();
+        assert_eq!(content.has_placeholders(), expected);
+    }
+
+    #[test_case("hp +")]
+    #[test_case("selected ? primary")]
+    #[test_case("'unclosed")]
+    fn test_invalid(input: &str) {
+        assert!(all_consuming(parse_expr::<VerboseError<_>>)(input.as_bytes()).is_err());
+    }
+
+    #[test_case("name - 1")]
+    #[test_case("name * hp")]
+    #[test_case("hp / 0" ; "division by zero")]
+    #[test_case("hp % 0" ; "remainder by zero")]
+    #[test_case("selected * 2")]
+    #[test_case("-name" ; "negated text")]
+    fn test_failed_arithmetic(input: &str) {
+        let (_, expr) = all_consuming(parse_expr::<VerboseError<_>>)(input.as_bytes()).unwrap();
+        assert_eq!(expr.eval(&props()), None);
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let (_, expr) = parse_expr::<VerboseError<_>>(b"missing + 1").unwrap();
+        assert_eq!(expr.eval(&props()), None);
+
+        let (_, value) = parse_interpolation::<VerboseError<_>>(b"{hp}/{missing}").unwrap();
+        assert_eq!(value.eval(&props()), None);
+        assert_eq!(value.eval_lossy(&props()), "42/");
+    }
+}
@@ -0,0 +1,415 @@
+use crate::{
+    bindings::{run_all, FunctionBindings},
+    build::{OnUiBlur, OnUiFocus, OnUiPress},
+    styles::UiDisabled,
+};
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+pub struct FocusPlugin;
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFocus>()
+            .register_type::<UiFocus>()
+            .register_type::<UiFocusable>()
+            .register_type::<UiFocused>()
+            .register_type::<UiTextInput>()
+            .add_systems(
+                Update,
+                (focus_on_press, navigate_focus, update_focus).chain(),
+            );
+    }
+}
+
+/// stick deflection to count as a direction
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// The currently focused node.
+///
+/// Set it to move the focus from code.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut, Reflect)]
+#[reflect(Resource)]
+pub struct UiFocus(pub Option<Entity>);
+
+/// A node that can receive the focus. Added to buttons and
+/// nodes with `focusable="true"` or a `tab_index`.
+///
+/// A negative `tab_index` is skipped by `Tab`, but still
+/// reachable with directional navigation. Disabled nodes
+/// cannot be focused.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[reflect]
+pub struct UiFocusable {
+    pub tab_index: i32,
+}
+
+/// marks the focused node, enables all `focus:` styles
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect]
+pub struct UiFocused;
+
+/// marks a node that takes typed text. While focused,
+/// `Enter`, `Space` and the arrow keys belong to the
+/// input and do not navigate. `Escape` blurs it.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect]
+pub struct UiTextInput;
+
+enum Navigation {
+    Next,
+    Previous,
+    Direction(Vec2),
+    Confirm,
+    Cancel,
+}
+
+fn focus_on_press(
+    mut focus: ResMut<UiFocus>,
+    interactions: Query<(Entity, Ref<Interaction>, Has<UiDisabled>), With<UiFocusable>>,
+) {
+    for (entity, interaction, disabled) in interactions.iter() {
+        if !disabled && interaction.is_changed() && *interaction == Interaction::Pressed {
+            focus.set_if_neq(UiFocus(Some(entity)));
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct NavigationInput<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    stick_neutral: Local<'s, bool>,
+}
+
+impl NavigationInput<'_, '_> {
+    fn read(&mut self, typing: bool) -> Option<Navigation> {
+        read_navigation(&self.keys, &self.gamepads, &mut self.stick_neutral, typing)
+    }
+}
+
+fn read_navigation(
+    keys: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+    stick_neutral: &mut bool,
+    typing: bool,
+) -> Option<Navigation> {
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    if keys.just_pressed(KeyCode::Tab) {
+        return Some(match shift {
+            true => Navigation::Previous,
+            false => Navigation::Next,
+        });
+    }
+
+    let keyboard = [
+        (KeyCode::ArrowUp, Vec2::NEG_Y),
+        (KeyCode::ArrowDown, Vec2::Y),
+        (KeyCode::ArrowLeft, Vec2::NEG_X),
+        (KeyCode::ArrowRight, Vec2::X),
+    ];
+
+    if let Some((_, dir)) = keyboard
+        .iter()
+        .filter(|_| !typing)
+        .find(|(key, _)| keys.just_pressed(*key))
+    {
+        return Some(Navigation::Direction(*dir));
+    }
+
+    if !typing && keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter, KeyCode::Space]) {
+        return Some(Navigation::Confirm);
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        return Some(Navigation::Cancel);
+    }
+
+    let dpad = [
+        (GamepadButton::DPadUp, Vec2::NEG_Y),
+        (GamepadButton::DPadDown, Vec2::Y),
+        (GamepadButton::DPadLeft, Vec2::NEG_X),
+        (GamepadButton::DPadRight, Vec2::X),
+    ];
+
+    let mut any_stick = false;
+    for gamepad in gamepads.iter() {
+        if let Some((_, dir)) = dpad
+            .iter()
+            .find(|(button, _)| gamepad.just_pressed(*button))
+        {
+            return Some(Navigation::Direction(*dir));
+        }
+
+        if gamepad.just_pressed(GamepadButton::South) {
+            return Some(Navigation::Confirm);
+        }
+
+        if gamepad.just_pressed(GamepadButton::East) {
+            return Some(Navigation::Cancel);
+        }
+
+        // ui space is y down
+        let stick = gamepad.left_stick() * Vec2::new(1., -1.);
+        if stick.length() < STICK_THRESHOLD {
+            continue;
+        }
+
+        any_stick = true;
+        if *stick_neutral {
+            *stick_neutral = false;
+            return Some(Navigation::Direction(stick.normalize()));
+        }
+    }
+
+    if !any_stick {
+        *stick_neutral = true;
+    }
+
+    None
+}
+
+fn navigate_focus(
+    mut cmd: Commands,
+    mut focus: ResMut<UiFocus>,
+    mut input: NavigationInput,
+    focusables: Query<
+        (
+            Entity,
+            &UiFocusable,
+            &ComputedNode,
+            &GlobalTransform,
+            &InheritedVisibility,
+        ),
+        Without<UiDisabled>,
+    >,
+    on_press: Query<&OnUiPress>,
+    text_inputs: Query<(), With<UiTextInput>>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    let typing = focus.is_some_and(|entity| text_inputs.contains(entity));
+    let Some(navigation) = input.read(typing) else {
+        return;
+    };
+
+    // visible nodes in tab order
+    let mut candidates = focusables
+        .iter()
+        .filter(|(_, _, computed, _, visibility)| visibility.get() && computed.size() != Vec2::ZERO)
+        .map(|(entity, focusable, _, transform, _)| {
+            (
+                entity,
+                focusable.tab_index,
+                transform.translation().truncate(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    candidates.sort_by(tab_order_cmp);
+
+    let tab_order = candidates
+        .iter()
+        .filter(|(_, index, _)| *index >= 0)
+        .map(|(entity, _, _)| *entity)
+        .collect::<Vec<_>>();
+
+    let current = focus.and_then(|focused| tab_order.iter().position(|e| *e == focused));
+
+    let next = match navigation {
+        Navigation::Next => match current {
+            Some(index) => tab_order.get((index + 1) % tab_order.len()).copied(),
+            None => tab_order.first().copied(),
+        },
+        Navigation::Previous => match current {
+            Some(index) => tab_order
+                .get((index + tab_order.len() - 1) % tab_order.len())
+                .copied(),
+            None => tab_order.last().copied(),
+        },
+        Navigation::Direction(dir) => {
+            let origin = focus.and_then(|focused| {
+                candidates
+                    .iter()
+                    .find(|(entity, _, _)| *entity == focused)
+                    .map(|(_, _, pos)| *pos)
+            });
+
+            match origin {
+                Some(origin) => closest_in_direction(origin, dir, &candidates).or(**focus),
+                None => tab_order.first().copied(),
+            }
+        }
+        Navigation::Confirm => {
+            if let Some(entity) = **focus {
+                if let Ok(funcs) = on_press.get(entity) {
+                    run_all(funcs, entity, &function_bindings, &mut cmd);
+                }
+            }
+            return;
+        }
+        Navigation::Cancel => None,
+    };
+
+    focus.set_if_neq(UiFocus(next));
+}
+
+/// html tab order, positive indices ascending first,
+/// then all `0` in layout order.
+fn tab_order_cmp(a: &(Entity, i32, Vec2), b: &(Entity, i32, Vec2)) -> std::cmp::Ordering {
+    let (_, a_index, a) = a;
+    let (_, b_index, b) = b;
+    (*a_index == 0)
+        .cmp(&(*b_index == 0))
+        .then(a_index.cmp(b_index))
+        .then(a.y.total_cmp(&b.y))
+        .then(a.x.total_cmp(&b.x))
+}
+
+/// closest node in direction, nodes off the axis
+/// count double.
+fn closest_in_direction(
+    origin: Vec2,
+    dir: Vec2,
+    candidates: &[(Entity, i32, Vec2)],
+) -> Option<Entity> {
+    candidates
+        .iter()
+        .filter_map(|(entity, _, pos)| {
+            let delta = *pos - origin;
+            let along = delta.dot(dir);
+            if along <= 1. {
+                return None;
+            }
+            let across = (delta - dir * along).length();
+            Some((*entity, along + across * 2.))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+/// moves the `UiFocused` marker and runs `on_focus`
+/// and `on_blur`.
+fn update_focus(
+    mut cmd: Commands,
+    mut focus: ResMut<UiFocus>,
+    mut previous: Local<Option<Entity>>,
+    focusables: Query<(), (With<UiFocusable>, Without<UiDisabled>)>,
+    on_focus: Query<&OnUiFocus>,
+    on_blur: Query<&OnUiBlur>,
+    function_bindings: Res<FunctionBindings>,
+) {
+    if focus.is_some_and(|entity| focusables.get(entity).is_err()) {
+        focus.set_if_neq(UiFocus(None));
+    }
+
+    if **focus == *previous {
+        return;
+    }
+
+    // a despawned node does not blur
+    if let Some(entity) = previous.take() {
+        if let Some(mut entity_cmd) = cmd.get_entity(entity) {
+            entity_cmd.remove::<UiFocused>();
+            if let Ok(funcs) = on_blur.get(entity) {
+                run_all(funcs, entity, &function_bindings, &mut cmd);
+            }
+        }
+    }
+
+    if let Some(entity) = **focus {
+        cmd.entity(entity).try_insert(UiFocused);
+        if let Ok(funcs) = on_focus.get(entity) {
+            run_all(funcs, entity, &function_bindings, &mut cmd);
+        }
+    }
+
+    *previous = **focus;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn test_closest_in_direction() {
+        let mut world = World::new();
+        let [a, b, c] = [(); 3].map(|_| world.spawn_empty().id());
+        let candidates = [
+            (a, 0, Vec2::new(0., 0.)),
+            (b, 0, Vec2::new(100., 0.)),
+            (c, 0, Vec2::new(30., 80.)),
+        ];
+
+        assert_eq!(
+            closest_in_direction(Vec2::ZERO, Vec2::X, &candidates),
+            Some(b)
+        );
+        assert_eq!(
+            closest_in_direction(Vec2::ZERO, Vec2::Y, &candidates),
+            Some(c)
+        );
+        assert_eq!(
+            closest_in_direction(Vec2::ZERO, Vec2::NEG_X, &candidates),
+            None
+        );
+    }
+
+    #[test]
+    fn test_typing_keeps_keys() {
+        let read = |typing: bool, key: KeyCode| {
+            let mut world = World::new();
+            let mut keys = ButtonInput::<KeyCode>::default();
+            keys.press(key);
+            world.insert_resource(keys);
+            world
+                .run_system_once(
+                    move |keys: Res<ButtonInput<KeyCode>>, gamepads: Query<&Gamepad>| {
+                        read_navigation(&keys, &gamepads, &mut true, typing)
+                    },
+                )
+                .unwrap()
+        };
+
+        assert!(read(true, KeyCode::ArrowDown).is_none());
+        assert!(read(true, KeyCode::Enter).is_none());
+        assert!(read(true, KeyCode::Space).is_none());
+        assert!(matches!(
+            read(true, KeyCode::Escape),
+            Some(Navigation::Cancel)
+        ));
+        assert!(matches!(read(true, KeyCode::Tab), Some(Navigation::Next)));
+        assert!(matches!(
+            read(false, KeyCode::ArrowDown),
+            Some(Navigation::Direction(Vec2::Y))
+        ));
+    }
+
+    #[test]
+    fn test_despawn_focused() {
+        let mut app = crate::test_app();
+        let node = app.world_mut().spawn(UiFocusable::default()).id();
+        app.world_mut().resource_mut::<UiFocus>().0 = Some(node);
+        app.update();
+        assert!(app.world().get::<UiFocused>(node).is_some());
+
+        app.world_mut().entity_mut(node).despawn_recursive();
+        app.update();
+        assert!(app.world().resource::<UiFocus>().is_none());
+    }
+
+    #[test]
+    fn test_tab_order() {
+        let mut world = World::new();
+        let [a, b, c, d] = [(); 4].map(|_| world.spawn_empty().id());
+        let mut candidates = [
+            (a, 0, Vec2::new(0., 0.)),
+            (b, 2, Vec2::new(0., 10.)),
+            (c, 0, Vec2::new(0., 20.)),
+            (d, 1, Vec2::new(0., 30.)),
+        ];
+
+        candidates.sort_by(tab_order_cmp);
+        let order = candidates.map(|(entity, _, _)| entity);
+        assert_eq!(order, [d, b, a, c]);
+    }
+}
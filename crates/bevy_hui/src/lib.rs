@@ -4,32 +4,53 @@
 
 use bevy::app::{App, Plugin};
 
+mod atlas;
 mod auto;
 mod bindings;
 mod build;
 mod compile;
 mod data;
 mod error;
+mod expr;
+mod focus;
 mod load;
 mod parse;
+mod reflect;
+mod shadow;
 mod styles;
+mod theme;
 mod util;
 
 pub mod prelude {
+    pub use crate::atlas::{AtlasLayouts, UiAtlasAnimation, DEFAULT_ATLAS_FPS};
     pub use crate::auto::{AutoLoadState, HuiAutoLoadPlugin};
     pub use crate::bindings::{
-        ComponentBindings, FunctionBindings, HtmlComponents, HtmlFunctions, UiChangedEvent,
+        ComponentBindings, FunctionBinding, FunctionBindings, HtmlBindings, HtmlComponents,
+        HtmlFunctions, HuiArgs, UiChangedEvent, UiDrag, UiScroll,
     };
     pub use crate::build::{
-        HtmlNode, OnUiChange, OnUiEnter, OnUiExit, OnUiPress, OnUiSpawn, Tags, TemplateProperties,
-        TemplateScope, UiId, UiTarget, UiWatch,
+        HtmlNode, OnUiBlur, OnUiChange, OnUiClick, OnUiDoubleClick, OnUiDrag, OnUiDragEnd,
+        OnUiDragStart, OnUiEnter, OnUiExit, OnUiFocus, OnUiPress, OnUiRelease, OnUiRightClick,
+        OnUiScroll, OnUiSpawn, Tags, TemplateProperties, TemplateScope, UiBind, UiId, UiTarget,
+        UiWatch,
     };
     pub use crate::compile::{CompileContextEvent, CompileNodeEvent};
-    pub use crate::data::{Action, Attribute, HtmlTemplate, NodeType, StyleAttr};
+    pub use crate::data::{
+        Action, AnimationRepeat, AtlasFrames, AtlasLayout, Attribute, FnCall, HtmlStyleSheet,
+        HtmlTemplate, Keyframe, KeyframeAnimation, Keyframes, MediaCompare, MediaQuery, NodeType,
+        StyleAttr, StyleTransition, TextShadow,
+    };
     pub use crate::error::ParseError;
     pub use crate::error::VerboseHtmlError;
+    pub use crate::focus::{UiFocus, UiFocusable, UiFocused, UiTextInput};
     pub use crate::parse::parse_template;
-    pub use crate::styles::{HoverTimer, HtmlStyle, InteractionTimer, PressedTimer, UiActive};
+    pub use crate::shadow::{TextShadowOf, UiTextShadow};
+    pub use crate::styles::{
+        DespawnAnimatedExt, EnterTimer, ExitTimer, HoverTimer, HtmlStyle, HuiBreakpoints,
+        InteractionTimer, PlayAnimationEvent, PressedTimer, StateTimers, StopAnimationEvent,
+        StyleTween, UiActive, UiAnimation, UiDespawning, UiDisabled, UiStates, UiStyleOwned,
+    };
+    pub use crate::theme::HuiTheme;
     pub use crate::HuiPlugin;
 }
 
@@ -42,6 +63,46 @@ impl Plugin for HuiPlugin {
             bindings::BindingPlugin,
             styles::TransitionPlugin,
             compile::CompilePlugin,
+            reflect::ReflectBindingPlugin,
+            theme::ThemePlugin,
+            focus::FocusPlugin,
+            shadow::TextShadowPlugin,
+            atlas::AtlasPlugin,
         ));
     }
 }
+
+/// headless app with the plugin, for tests that run systems.
+#[cfg(test)]
+pub(crate) fn test_app() -> App {
+    use bevy::prelude::*;
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        bevy::input::InputPlugin,
+        bevy::state::app::StatesPlugin,
+        HuiPlugin,
+    ))
+    .init_asset::<Font>()
+    .init_asset::<TextureAtlasLayout>();
+    app
+}
+
+/// parses and adds the template asset.
+#[cfg(test)]
+pub(crate) fn add_template(app: &mut App, input: &str) -> bevy::asset::Handle<data::HtmlTemplate> {
+    let (_, template) = parse::parse_template::<nom::error::VerboseError<&[u8]>>(input.as_bytes())
+        .expect("valid template");
+    app.world_mut()
+        .resource_mut::<bevy::asset::Assets<data::HtmlTemplate>>()
+        .add(template)
+}
+
+/// adds the template and spawns it as root node.
+#[cfg(test)]
+pub(crate) fn spawn_template(app: &mut App, input: &str) -> bevy::ecs::entity::Entity {
+    let handle = add_template(app, input);
+    app.world_mut().spawn(build::HtmlNode(handle)).id()
+}
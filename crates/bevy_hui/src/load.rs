@@ -1,4 +1,8 @@
-use crate::{data::HtmlTemplate, error::ParseError, parse::parse_template};
+use crate::{
+    data::{HtmlStyleSheet, HtmlTemplate},
+    error::ParseError,
+    parse::{parse_stylesheet, parse_template},
+};
 use bevy::{
     asset::{io::Reader, AssetLoader},
     prelude::*,
@@ -8,7 +12,9 @@ pub struct LoaderPlugin;
 impl Plugin for LoaderPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<HtmlTemplate>();
+        app.init_asset::<HtmlStyleSheet>();
         app.init_asset_loader::<HtmlAssetLoader>();
+        app.init_asset_loader::<HssAssetLoader>();
     }
 }
 
@@ -33,7 +39,14 @@ impl AssetLoader for HtmlAssetLoader {
 
         let file_path = load_context.path().to_str().unwrap_or_default();
         match parse_template::<crate::error::VerboseHtmlError>(&bytes) {
-            Ok((_, template)) => Ok(template),
+            Ok((_, mut template)) => {
+                template.stylesheets = template
+                    .style_paths
+                    .iter()
+                    .map(|path| load_context.load(path))
+                    .collect();
+                Ok(template)
+            }
             Err(err) => match err {
                 nom::Err::Incomplete(_) => Err(ParseError::Incomplete),
                 nom::Err::Error(err) | nom::Err::Failure(err) => {
@@ -47,3 +60,41 @@ impl AssetLoader for HtmlAssetLoader {
         &["html", "xml"]
     }
 }
+
+/// loads `.hss` stylesheets, used in templates
+/// with `<style src="main.hss"/>`
+#[derive(Default)]
+pub struct HssAssetLoader;
+impl AssetLoader for HssAssetLoader {
+    type Asset = HtmlStyleSheet;
+    type Settings = ();
+    type Error = ParseError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|err| ParseError::FailedToRead(err.to_string()))?;
+
+        let file_path = load_context.path().to_str().unwrap_or_default();
+        match parse_stylesheet::<crate::error::VerboseHtmlError>(&bytes) {
+            Ok((_, sheet)) => Ok(sheet),
+            Err(err) => match err {
+                nom::Err::Incomplete(_) => Err(ParseError::Incomplete),
+                nom::Err::Error(err) | nom::Err::Failure(err) => {
+                    Err(ParseError::Nom(err.format(&bytes, file_path)))
+                }
+            },
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["hss"]
+    }
+}
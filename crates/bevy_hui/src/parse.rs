@@ -1,9 +1,15 @@
-use crate::data::{Action, AttrTokens, Attribute, HtmlTemplate, StyleAttr, XNode};
+use crate::data::{
+    Action, AnimationRepeat, AtlasFrames, AtlasLayout, AttrTokens, Attribute, Branch, FnCall,
+    ForEach, HtmlStyleSheet, HtmlTemplate, Keyframe, KeyframeAnimation, Keyframes, MediaCompare,
+    MediaQuery, Selector, StyleAttr, StyleRule, StyleTransition, TextShadow, XNode,
+};
+use crate::expr::{parse_ident, parse_interpolation, parse_placeholder};
 use crate::prelude::NodeType;
 use crate::util::SlotMap;
-use bevy::math::{Rect, Vec2};
-use bevy::prelude::EaseFunction;
+use bevy::math::{Rect, UVec2, Vec2};
+use bevy::prelude::{EaseFunction, Visibility};
 use bevy::sprite::{BorderRect, SliceScaleMode, TextureSlicer};
+use bevy::text::{FontSmoothing, JustifyText, LineBreak};
 use bevy::ui::widget::NodeImageMode;
 use bevy::ui::{
     AlignContent, AlignItems, AlignSelf, Display, FlexDirection, FlexWrap, GridAutoFlow,
@@ -18,12 +24,12 @@ use bevy::{
 use nom::{
     branch::alt,
     bytes::complete::{is_not, tag, take_until, take_while, take_while1, take_while_m_n},
-    character::complete::multispace0,
-    combinator::{complete, map, map_parser, not, rest},
+    character::complete::{multispace0, multispace1},
+    combinator::{all_consuming, complete, cut, map, map_parser, not, opt, recognize, rest},
     error::{context, ContextError, ErrorKind, ParseError},
-    multi::{many0, separated_list1},
+    multi::{many0, many1, separated_list0, separated_list1},
     number::complete::float,
-    sequence::{delimited, preceded, terminated, tuple},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
     IResult, Parser,
 };
 
@@ -55,6 +61,9 @@ where
     let mut properties = HashMap::default();
     let mut root = vec![];
     let mut content = SlotMap::<String>::default();
+    let mut style = HtmlStyleSheet::default();
+    let mut style_paths = vec![];
+    let mut keyframes = HashMap::default();
 
     for child in xml.children.drain(..) {
         match child.name {
@@ -77,6 +86,31 @@ where
                     name = Some(str_name);
                 };
             }
+            b"style" => {
+                if let Some(src) = child
+                    .attributes
+                    .iter()
+                    .find_map(|attr| (attr.key == b"src").then_some(attr.value))
+                {
+                    style_paths.push(String::from_utf8_lossy(src).to_string());
+                }
+                if let Some(value) = child.value {
+                    let (_, sheet) = parse_stylesheet(value)?;
+                    style.rules.extend(sheet.rules);
+                }
+            }
+            b"keyframes" => {
+                if let (Some(key), Some(value)) = (
+                    child
+                        .attributes
+                        .iter()
+                        .find_map(|attr| (attr.key == b"name").then_some(attr.value)),
+                    child.value,
+                ) {
+                    let (_, frames) = parse_keyframes(value)?;
+                    keyframes.insert(String::from_utf8_lossy(key).to_string(), frames);
+                }
+            }
             _ => {
                 let (_, node) = from_raw_xml::<E>(child, &mut content)?;
                 root.push(node);
@@ -91,10 +125,207 @@ where
             properties,
             root,
             content,
+            style,
+            style_paths,
+            stylesheets: vec![],
+            keyframes,
         },
     ))
 }
 
+/// parses the rules of a `<style>` block or `.hss` file
+///
+/// `.card, button:hover { background: #333; hover:padding: 5px; }`
+pub fn parse_stylesheet<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], HtmlStyleSheet, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, rules) = all_consuming(terminated(
+        many0(preceded(trim_css_comments0, parse_style_rules)),
+        trim_css_comments0,
+    ))(input)?;
+
+    Ok((
+        input,
+        HtmlStyleSheet {
+            rules: rules.into_iter().flatten().collect(),
+        },
+    ))
+}
+
+fn trim_css_comments0<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], (), E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    map(
+        tuple((
+            multispace0,
+            many0(terminated(
+                delimited(tag("/*"), take_until("*/"), tag("*/")),
+                multispace0,
+            )),
+        )),
+        |_| (),
+    )(input)
+}
+
+/// parses the stops of a `<keyframes>` block
+///
+/// `0% { background: #333; } 50% { width: 10px; } to { background: #555; }`
+pub fn parse_keyframes<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Keyframes, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let stop = tuple((
+        preceded(
+            trim_css_comments0,
+            alt((
+                map(terminated(parse_float, tag("%")), |percent| percent / 100.),
+                map(tag("from"), |_| 0.),
+                map(tag("to"), |_| 1.),
+            )),
+        ),
+        context(
+            "Is not a valid keyframe, try `50% { background: #FFF; }`",
+            cut(delimited(
+                preceded(multispace0, tag("{")),
+                many0(preceded(trim_css_comments0, parse_declaration)),
+                preceded(trim_css_comments0, tag("}")),
+            )),
+        ),
+    ));
+
+    let (input, stops) = all_consuming(terminated(many0(stop), trim_css_comments0))(input)?;
+
+    let mut keyframes = Keyframes::default();
+    for (at, declarations) in stops {
+        let mut styles = vec![];
+        for (prefix, key, value) in declarations {
+            let (_, style) = cut(|v| parse_style(prefix, key, v))(value)?;
+            styles.push(style);
+        }
+        keyframes.stops.push(Keyframe {
+            at: at.clamp(0., 1.),
+            styles,
+        });
+    }
+
+    keyframes.stops.sort_by(|a, b| a.at.total_cmp(&b.at));
+    Ok((input, keyframes))
+}
+
+// `.a, b:hover { .. }` one rule per selector
+fn parse_style_rules<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vec<StyleRule>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, selectors) = separated_list1(
+        delimited(multispace0, tag(","), multispace0),
+        parse_selector,
+    )(input)?;
+
+    let (input, declarations) = context(
+        "Is not a valid style rule, try `.class { background: #FFF; }`",
+        cut(delimited(
+            preceded(multispace0, tag("{")),
+            many0(preceded(trim_css_comments0, parse_declaration)),
+            preceded(trim_css_comments0, tag("}")),
+        )),
+    )(input)?;
+
+    let mut rules = vec![];
+    for (selector, pseudo) in selectors {
+        let mut styles = vec![];
+        for &(prefix, key, value) in declarations.iter() {
+            let (_, style) = cut(|v| parse_style(prefix.or(pseudo), key, v))(value)?;
+            styles.push(style);
+        }
+        rules.push(StyleRule { selector, styles });
+    }
+
+    Ok((input, rules))
+}
+
+/// a selector and its optional state `:hover`
+type PrefixedSelector<'a> = (Selector, Option<&'a [u8]>);
+
+// `button.primary#title:hover`
+fn parse_selector<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], PrefixedSelector<'a>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let take_name = |i| take_while1(|b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')(i);
+    let (input, tag_name) = take_snake(input)?;
+    let (input, parts) = many0(tuple((alt((tag("."), tag("#"))), take_name)))(input)?;
+
+    if tag_name.is_empty() && parts.is_empty() {
+        let err = E::from_error_kind(input, ErrorKind::Alpha);
+        return Err(nom::Err::Error(E::add_context(
+            input,
+            "Is not a valid selector, try `.class` `#id` or `tag`",
+            err,
+        )));
+    }
+
+    // `:hover` or a custom state `:state(checked)`
+    let (input, pseudo) = opt(preceded(
+        tag(":"),
+        alt((
+            take_state_prefix,
+            take_while1(|b: u8| b.is_ascii_alphabetic() || b == b'_'),
+        )),
+    ))(input)?;
+
+    if let Some(pseudo) = pseudo.filter(|pseudo| !is_style_prefix(pseudo)) {
+        return Err(unknown_prefix(pseudo));
+    }
+
+    let mut selector = Selector {
+        tag: (!tag_name.is_empty()).then(|| String::from_utf8_lossy(tag_name).to_string()),
+        ..Default::default()
+    };
+
+    for (kind, name) in parts {
+        let name = String::from_utf8_lossy(name).to_string();
+        match kind {
+            b"#" => selector.id = Some(name),
+            _ => selector.classes.push(name),
+        }
+    }
+
+    Ok((input, (selector, pseudo)))
+}
+
+/// the optional prefix, key and value of a declaration
+type Declaration<'a> = (Option<&'a [u8]>, &'a [u8], &'a [u8]);
+
+// `background: #FFF;` or `hover:background: #FFF;`
+fn parse_declaration<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Declaration<'a>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let take_key = |i| take_while1(|b: u8| b.is_ascii_alphabetic() || b == b'_')(i);
+    let (input, (prefix, key)) = alt((
+        map(
+            tuple((
+                alt((take_media_prefix, take_state_prefix, take_key)),
+                tag(":"),
+                take_key,
+                multispace0,
+                tag(":"),
+            )),
+            |(prefix, _, key, _, _)| (Some(prefix), key),
+        ),
+        map(tuple((take_key, multispace0, tag(":"))), |(key, _, _)| {
+            (None, key)
+        }),
+    ))(input)?;
+
+    let (input, value) = preceded(multispace0, take_while1(|b: u8| b != b';' && b != b'}'))(input)?;
+    let (input, _) = opt(tag(";"))(input)?;
+    Ok((input, (prefix, key, value.trim_ascii_end())))
+}
+
 fn trim_comments0<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vec<&'a [u8]>, E>
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
@@ -124,18 +355,39 @@ where
     let (_, node_type) = parse_node_type(xml.name)?;
     xnode.node_type = node_type;
 
+    if xml.name == b"else" {
+        xnode.node_type = NodeType::Node;
+        xnode.branch = Some(Branch::Else);
+    }
+
+    if let Some(content) = xml.value {
+        all_consuming(parse_interpolation)(content)?;
+    }
+
     xnode.content_id = xml
         .value
         .map(|bytes| String::from_utf8_lossy(bytes).to_string())
+        .map(|raw| match xnode.node_type {
+            NodeType::Span => collapse_whitespace(&raw),
+            _ => raw,
+        })
         .map(|raw| content_map.insert(raw))
         .unwrap_or_default();
 
     for attr in xml.attributes.iter() {
+        // `<slot name="footer">` names the outlet
+        if matches!(xnode.node_type, NodeType::Slot) && attr.key == b"name" {
+            let (_, name) = as_string(attr.value)?;
+            xnode.name = Some(name);
+            continue;
+        }
+
         let (_input, compiled_attr) = match xnode.node_type {
             NodeType::Custom(_) => {
                 match attribute_from_parts::<E>(attr.prefix, attr.key, attr.value) {
                     Ok(attr) => attr,
-                    Err(_) => as_prop(attr.key, attr.value)?,
+                    Err(nom::Err::Error(_)) => as_prop(attr.key, attr.value)?,
+                    Err(err) => return Err(err),
                 }
             }
             _ => attribute_from_parts(attr.prefix, attr.key, attr.value)?,
@@ -156,6 +408,19 @@ where
                 xnode.tags.insert(key, val);
             }
             Attribute::Watch(watch_id) => xnode.watch = Some(watch_id),
+            Attribute::Each(each) => xnode.each = Some(each),
+            Attribute::Branch(branch) => xnode.branch = Some(branch),
+            Attribute::Bind(bind) => xnode.bind = Some(bind),
+            Attribute::Slot(slot) => xnode.slot = Some(slot),
+            Attribute::Class(classes) => xnode.classes.extend(classes),
+            Attribute::Focusable(focusable) => xnode.focusable = Some(focusable),
+            Attribute::TabIndex(index) => xnode.tab_index = Some(index),
+            Attribute::Disabled(disabled) => xnode.disabled = Some(disabled),
+            Attribute::Animation(animation) => xnode.animation = Some(animation),
+            Attribute::AtlasLayout(layout) => xnode.atlas_layout = Some(layout),
+            Attribute::AtlasIndex(index) => xnode.atlas_index = Some(index),
+            Attribute::AtlasFrames(frames) => xnode.atlas_frames = Some(frames),
+            Attribute::Fps(fps) => xnode.fps = Some(fps),
         }
     }
 
@@ -228,13 +493,17 @@ where
         ));
     }
 
-    let (input, children) = many0(parse_xml_node)(input)?;
-
-    let (input, _) = trim_comments0(input)?;
-
-    let (input, value) = map(take_while(|b: u8| b != b'<'), |c: &[u8]| {
-        (c.len() > 0).then_some(c)
-    })(input)?;
+    let (input, (children, value)) = match start_name {
+        b"text" | b"span" => parse_text_content(input)?,
+        _ => {
+            let (input, children) = many0(parse_xml_node)(input)?;
+            let (input, _) = trim_comments0(input)?;
+            let (input, value) = map(take_while(|b: u8| b != b'<'), |c: &[u8]| {
+                (c.len() > 0).then_some(c)
+            })(input)?;
+            (input, (children, value))
+        }
+    };
 
     let (input, (end_prefix, end_name)) = parse_xml_end(input)?;
     if start_name != end_name || prefix != end_prefix {
@@ -257,7 +526,109 @@ where
     ))
 }
 
-fn parse_xml_end<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], (Option<&'a [u8]>, &'a [u8]), E>
+/// the span children and the value of a text
+type TextContent<'a> = (Vec<Xml<'a>>, Option<&'a [u8]>);
+
+enum TextPart<'a> {
+    Run(&'a [u8]),
+    Node(Xml<'a>),
+}
+
+// `Hello <span font_color="#F00">{name}</span>!`, a single run is the
+// value. Mixed with nodes, each run becomes an anonymous `<span>`.
+fn parse_text_content<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], TextContent<'a>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, parts) = many0(alt((
+        map(take_while1(|b: u8| b != b'<'), |run| {
+            Some(TextPart::Run(run))
+        }),
+        map(parse_comment, |_| None),
+        map(parse_xml_node, |node| Some(TextPart::Node(node))),
+    )))(input)?;
+
+    // comments drop out, without doubling the whitespace around them
+    let mut after_comment = false;
+    let mut merged: Vec<TextPart> = vec![];
+    for part in parts {
+        match part {
+            None => after_comment = true,
+            Some(TextPart::Run(run)) if std::mem::take(&mut after_comment) => {
+                let spaced = matches!(
+                    merged.last(),
+                    Some(TextPart::Run(previous)) if previous.last().is_some_and(u8::is_ascii_whitespace)
+                );
+                merged.push(TextPart::Run(match spaced {
+                    true => run.trim_ascii_start(),
+                    false => run,
+                }));
+            }
+            Some(part) => {
+                after_comment = false;
+                merged.push(part);
+            }
+        }
+    }
+
+    let mut parts = merged;
+    if let [TextPart::Run(run)] = parts.as_slice() {
+        return Ok((input, (vec![], Some(*run))));
+    }
+
+    // whitespace around the content is not part of the text
+    let last = parts.len().saturating_sub(1);
+    if let Some(TextPart::Run(run)) = parts.first_mut() {
+        *run = run.trim_ascii_start();
+    }
+    if let Some(TextPart::Run(run)) = parts.get_mut(last) {
+        *run = run.trim_ascii_end();
+    }
+
+    let children = parts
+        .into_iter()
+        .filter_map(|part| match part {
+            TextPart::Run([]) => None,
+            TextPart::Run(run) => Some(Xml {
+                prefix: None,
+                name: b"span",
+                value: Some(run),
+                attributes: vec![],
+                children: vec![],
+            }),
+            TextPart::Node(node) => Some(node),
+        })
+        .collect();
+
+    Ok((input, (children, None)))
+}
+
+// html like, whitespace runs become a single space
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+
+        if std::mem::take(&mut space) {
+            out.push(' ');
+        }
+        out.push(c);
+    }
+
+    if space {
+        out.push(' ');
+    }
+    out
+}
+
+/// the optional prefix and name of a tag
+type TagName<'a> = (Option<&'a [u8]>, &'a [u8]);
+
+fn parse_xml_end<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], TagName<'a>, E>
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
 {
@@ -291,7 +662,9 @@ where
         map(tag("button"), |_| NodeType::Button),
         map(tag("text"), |_| NodeType::Text),
         map(tag("slot"), |_| NodeType::Slot),
+        map(all_consuming(tag("span")), |_| NodeType::Span),
         map(tag("template"), |_| NodeType::Template),
+        map(all_consuming(tag("for")), |_| NodeType::For),
         map(rest, |val| {
             let custom = String::from_utf8_lossy(val).to_string();
             NodeType::Custom(custom)
@@ -299,22 +672,28 @@ where
     ))(input)
 }
 
-fn parse_uncompiled<'a>(
+fn parse_uncompiled<'a, E>(
     prefix: Option<&'a [u8]>,
     key: &'a [u8],
     value: &'a [u8],
-) -> Option<Attribute> {
-    let result: IResult<&[u8], &[u8]> = delimited(tag("{"), is_not("}"), tag("}"))(value);
-    match result {
-        Ok((_, prop)) => {
-            return Some(Attribute::Uncompiled(AttrTokens {
-                prefix: prefix.map(|p| String::from_utf8_lossy(p).to_string()),
-                ident: String::from_utf8_lossy(key).to_string(),
-                key: String::from_utf8_lossy(prop).to_string(),
-            }));
-        }
-        Err(_) => None,
+) -> IResult<&'a [u8], Option<Attribute>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    if !value.contains(&b'{') {
+        return Ok((value, None));
     }
+
+    let (input, interpolation) = all_consuming(parse_interpolation)(value)?;
+    Ok((
+        input,
+        Some(Attribute::Uncompiled(AttrTokens {
+            prefix: prefix.map(|p| String::from_utf8_lossy(p).to_string()),
+            ident: String::from_utf8_lossy(key).to_string(),
+            raw: String::from_utf8_lossy(value).to_string(),
+            value: interpolation,
+        })),
+    ))
 }
 
 pub(crate) fn as_prop<'a, E>(key: &'a [u8], value: &'a [u8]) -> IResult<&'a [u8], Attribute, E>
@@ -334,7 +713,30 @@ pub(crate) fn attribute_from_parts<'a, E>(
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
 {
-    if let Some(attr) = parse_uncompiled(prefix, key, value) {
+    if key == b"each" {
+        let (_, each) = parse_each(value)?;
+        return Ok((key, Attribute::Each(each)));
+    }
+
+    if key == b"bind" {
+        let (_, bind) = parse_bind(value)?;
+        return Ok((key, Attribute::Bind(bind)));
+    }
+
+    if key == b"if" || key == b"else_if" {
+        let (_, condition) = all_consuming(parse_placeholder)(value)?;
+        let branch = match key {
+            b"if" => Branch::If(condition),
+            _ => Branch::ElseIf(condition),
+        };
+        return Ok((key, Attribute::Branch(branch)));
+    }
+
+    if let Some(prefix) = prefix.filter(|p| *p != b"tag" && !is_style_prefix(p)) {
+        return Err(unknown_prefix(prefix));
+    }
+
+    if let (_, Some(attr)) = parse_uncompiled(prefix, key, value)? {
         return Ok((b"", attr));
     }
 
@@ -357,30 +759,117 @@ where
             let (_, val) = as_string(value)?;
             Ok((key, Attribute::Target(val)))
         }
+        b"slot" => {
+            let (_, val) = as_string(value)?;
+            Ok((key, Attribute::Slot(val)))
+        }
+        b"class" => {
+            let (_, val) = as_string(value)?;
+            let classes = val.split_whitespace().map(String::from).collect();
+            Ok((key, Attribute::Class(classes)))
+        }
         b"src" => {
             let (_, val) = as_string(value)?;
             Ok((key, Attribute::Path(val)))
         }
+        b"focusable" => {
+            let (_, focusable) = all_consuming(parse_bool)(value)?;
+            Ok((key, Attribute::Focusable(focusable)))
+        }
+        b"tab_index" => {
+            let (_, index) = context(
+                "tab_index has to be a number",
+                all_consuming(nom::character::complete::i32),
+            )(value)?;
+            Ok((key, Attribute::TabIndex(index)))
+        }
+        b"disabled" => {
+            let (_, disabled) = all_consuming(parse_bool)(value)?;
+            Ok((key, Attribute::Disabled(disabled)))
+        }
+        b"animation" => {
+            let (_, animation) = parse_animation(value)?;
+            Ok((key, Attribute::Animation(animation)))
+        }
+        b"atlas_layout" => {
+            let (_, layout) = parse_atlas_layout(value)?;
+            Ok((key, Attribute::AtlasLayout(layout)))
+        }
+        b"atlas_index" => {
+            let (_, index) = context(
+                "atlas_index has to be a positive number",
+                all_consuming(nom::character::complete::u32),
+            )(value)?;
+            Ok((key, Attribute::AtlasIndex(index as usize)))
+        }
+        b"atlas_frames" => {
+            let (_, frames) = parse_atlas_frames(value)?;
+            Ok((key, Attribute::AtlasFrames(frames)))
+        }
+        b"fps" => {
+            let (_, fps) = context("fps has to be a number", all_consuming(parse_float))(value)?;
+            Ok((key, Attribute::Fps(fps)))
+        }
         b"on_enter" => {
-            let (_, list) = as_string_list(value)?;
+            let (_, list) = as_fn_calls(value)?;
             Ok((key, Attribute::Action(Action::OnEnter(list))))
         }
         b"on_exit" => {
-            let (_, list) = as_string_list(value)?;
+            let (_, list) = as_fn_calls(value)?;
             Ok((key, Attribute::Action(Action::OnExit(list))))
         }
         b"on_press" => {
-            let (_, list) = as_string_list(value)?;
+            let (_, list) = as_fn_calls(value)?;
             Ok((key, Attribute::Action(Action::OnPress(list))))
         }
         b"on_spawn" => {
-            let (_, list) = as_string_list(value)?;
+            let (_, list) = as_fn_calls(value)?;
             Ok((key, Attribute::Action(Action::OnSpawn(list))))
         }
         b"on_change" => {
-            let (_, list) = as_string_list(value)?;
+            let (_, list) = as_fn_calls(value)?;
             Ok((key, Attribute::Action(Action::OnChange(list))))
         }
+        b"on_release" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnRelease(list))))
+        }
+        b"on_click" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnClick(list))))
+        }
+        b"on_double_click" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnDoubleClick(list))))
+        }
+        b"on_right_click" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnRightClick(list))))
+        }
+        b"on_drag_start" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnDragStart(list))))
+        }
+        b"on_drag" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnDrag(list))))
+        }
+        b"on_drag_end" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnDragEnd(list))))
+        }
+        b"on_scroll" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnScroll(list))))
+        }
+        b"on_focus" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnFocus(list))))
+        }
+        b"on_blur" => {
+            let (_, list) = as_fn_calls(value)?;
+            Ok((key, Attribute::Action(Action::OnBlur(list))))
+        }
         _ => {
             let (_, style) = parse_style(prefix, key, value)?;
             Ok((key, Attribute::Style(style)))
@@ -388,6 +877,256 @@ where
     }
 }
 
+// `@w<800`, `@h>=600` or a named breakpoint `@sm`
+fn take_media_prefix<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    recognize(preceded(
+        tag("@"),
+        take_while1(|b: u8| b.is_ascii_alphanumeric() || b"_.<>=".contains(&b)),
+    ))(input)
+}
+
+// `w<800`, `h>=600` or `sm`
+fn parse_media_query<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], MediaQuery, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let compare = alt((
+        map(tag("<="), |_| MediaCompare::LessEqual),
+        map(tag(">="), |_| MediaCompare::GreaterEqual),
+        map(tag("<"), |_| MediaCompare::Less),
+        map(tag(">"), |_| MediaCompare::Greater),
+    ));
+
+    alt((
+        map(
+            tuple((alt((tag("w"), tag("h"))), compare, parse_float)),
+            |(axis, compare, value)| match axis {
+                b"w" => MediaQuery::Width(compare, value),
+                _ => MediaQuery::Height(compare, value),
+            },
+        ),
+        map(
+            take_while1(|b: u8| b.is_ascii_alphanumeric() || b == b'_'),
+            |name| MediaQuery::Breakpoint(String::from_utf8_lossy(name).to_string()),
+        ),
+    ))(input)
+}
+
+// background 100ms linear, width 200ms/400ms cubic_out
+fn parse_transitions<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vec<StyleTransition>, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let take_name = |i| take_while1(|b: u8| b.is_ascii_alphabetic() || b == b'_')(i);
+    let (input, parts) = context(
+        "Is not a valid transition, try `background 100ms linear, width 200ms/400ms`",
+        all_consuming(separated_list1(
+            tag(","),
+            delimited(
+                multispace0,
+                tuple((
+                    take_name,
+                    preceded(multispace1, parse_delay),
+                    opt(preceded(tag("/"), parse_delay)),
+                    opt(preceded(multispace1, take_name)),
+                )),
+                multispace0,
+            ),
+        )),
+    )(input)?;
+
+    let mut transitions = vec![];
+    for (property, duration, duration_out, easing) in parts {
+        let easing = match easing {
+            Some(easing) => Some(parse_easing(easing)?.1),
+            None => None,
+        };
+
+        transitions.push(StyleTransition {
+            property: String::from_utf8_lossy(property).to_string(),
+            duration,
+            duration_out,
+            easing,
+        });
+    }
+
+    Ok((input, transitions))
+}
+
+// pulse 1.2s infinite alternate sine_in_out
+fn parse_animation<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], KeyframeAnimation, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, (name, options)) = context(
+        "Is not a valid animation, try `pulse 1s infinite alternate linear`",
+        all_consuming(delimited(
+            multispace0,
+            tuple((
+                take_while1(|b: u8| b.is_ascii_alphabetic() || b == b'_' || b == b'-'),
+                many0(preceded(
+                    multispace1,
+                    take_while1(|b: u8| !b.is_ascii_whitespace()),
+                )),
+            )),
+            multispace0,
+        )),
+    )(input)?;
+
+    let mut animation = KeyframeAnimation::new(String::from_utf8_lossy(name), 1.);
+    for option in options {
+        let duration = all_consuming(alt((
+            map(terminated(parse_float::<E>, tag("ms")), |v| v / 1000.),
+            terminated(parse_float, tag("s")),
+        )))(option);
+
+        if let Ok((_, duration)) = duration {
+            animation.duration = duration;
+            continue;
+        }
+
+        if let Ok((_, count)) = all_consuming(nom::character::complete::u32::<_, E>)(option) {
+            animation.repeat = AnimationRepeat::Count(count);
+            continue;
+        }
+
+        match option {
+            b"infinite" => animation.repeat = AnimationRepeat::Infinite,
+            b"alternate" => animation.alternate = true,
+            _ => {
+                let (_, easing) = parse_easing(option)?;
+                animation.easing = Some(easing);
+            }
+        }
+    }
+
+    Ok((input, animation))
+}
+
+// tile(32,32) cols(8) rows(4) padding(2,2) offset(0,0)
+fn parse_atlas_layout<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], AtlasLayout, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    const HINT: &str = "Is not a valid atlas_layout, try `tile(32,32) cols(8) rows(4)`";
+
+    let (input, options) = context(
+        HINT,
+        all_consuming(many1(delimited(
+            multispace0,
+            tuple((
+                take_while1(|b: u8| b.is_ascii_alphabetic()),
+                delimited(
+                    tag("("),
+                    separated_list1(
+                        tuple((multispace0, tag(","), multispace0)),
+                        nom::character::complete::u32,
+                    ),
+                    tag(")"),
+                ),
+            )),
+            multispace0,
+        ))),
+    )(input)?;
+
+    let mut layout = AtlasLayout::default();
+    for (option, args) in options {
+        match (option, args.as_slice()) {
+            (b"tile", [x, y]) => layout.tile_size = UVec2::new(*x, *y),
+            (b"cols", [columns]) => layout.columns = *columns,
+            (b"rows", [rows]) => layout.rows = *rows,
+            (b"padding", [x, y]) => layout.padding = Some(UVec2::new(*x, *y)),
+            (b"offset", [x, y]) => layout.offset = Some(UVec2::new(*x, *y)),
+            _ => {
+                return Err(nom::Err::Failure(E::add_context(
+                    option,
+                    HINT,
+                    E::from_error_kind(option, ErrorKind::Tag),
+                )))
+            }
+        }
+    }
+
+    if layout.tile_size == UVec2::ZERO {
+        return Err(nom::Err::Failure(E::add_context(
+            input,
+            "atlas_layout is missing a `tile(width,height)`",
+            E::from_error_kind(input, ErrorKind::Verify),
+        )));
+    }
+
+    Ok((input, layout))
+}
+
+// 0..7
+fn parse_atlas_frames<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], AtlasFrames, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, (first, last)) = context(
+        "Is not a valid atlas_frames, try `0..7`",
+        all_consuming(delimited(
+            multispace0,
+            separated_pair(
+                nom::character::complete::u32,
+                delimited(multispace0, tag(".."), multispace0),
+                nom::character::complete::u32,
+            ),
+            multispace0,
+        )),
+    )(input)?;
+
+    Ok((
+        input,
+        AtlasFrames {
+            first: first.min(last) as usize,
+            last: first.max(last) as usize,
+        },
+    ))
+}
+
+// item in {items}
+fn parse_each<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], ForEach, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "Is not a valid loop, try `item in {items}`",
+        map(
+            tuple((
+                preceded(multispace0, take_snake),
+                delimited(multispace1, tag("in"), multispace1),
+                delimited(
+                    tag("{"),
+                    delimited(multispace0, take_snake, multispace0),
+                    tag("}"),
+                ),
+            )),
+            |(binding, _, source)| ForEach {
+                binding: String::from_utf8_lossy(binding).to_string(),
+                source: String::from_utf8_lossy(source).to_string(),
+            },
+        ),
+    )(input)
+}
+
+fn parse_bind<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], String, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "Can only bind to a single property, try `{key}`",
+        all_consuming(delimited(
+            tag("{"),
+            delimited(multispace0, parse_ident, multispace0),
+            tag("}"),
+        )),
+    )(input)
+}
+
 #[rustfmt::skip]
 fn parse_style<'a, E>(
     prefix: Option<&'a [u8]>,
@@ -413,12 +1152,19 @@ where
         b"border_color" => map(parse_color, StyleAttr::BorderColor)(value)?,
         b"font" => map(as_string, StyleAttr::Font)(value)?,
         b"font_color" => map(parse_color, StyleAttr::FontColor)(value)?,
+        b"text_justify" => map(parse_text_justify, StyleAttr::TextJustify)(value)?,
+        b"line_break" => map(parse_line_break, StyleAttr::LineBreak)(value)?,
+        b"font_smoothing" => map(parse_font_smoothing, StyleAttr::FontSmoothing)(value)?,
+        b"text_shadow" => map(parse_text_shadow, StyleAttr::TextShadow)(value)?,
         b"font_size" => map(parse_float, StyleAttr::FontSize)(value)?,
         b"max_height" => map(parse_val, StyleAttr::MaxHeight)(value)?,
         b"max_width" => map(parse_val, StyleAttr::MaxWidth)(value)?,
         b"min_height" => map(parse_val, StyleAttr::MinHeight)(value)?,
         b"min_width" => map(parse_val, StyleAttr::MinWidth)(value)?,
+        b"aspect_ratio" => map(parse_float, StyleAttr::AspectRatio)(value)?,
         b"delay" => map(parse_delay, StyleAttr::Delay)(value)?,
+        b"delay_out" => map(parse_delay, StyleAttr::DelayOut)(value)?,
+        b"transition" => map(parse_transitions, StyleAttr::Transition)(value)?,
         b"ease" => map(parse_easing, StyleAttr::Easing)(value)?,
         b"image_region" => map(parse_rect, StyleAttr::ImageRegion)(value)?,
         b"position" => map(parse_position_type, StyleAttr::Position)(value)?,
@@ -426,6 +1172,11 @@ where
         b"overflow" => map(parse_overflow, StyleAttr::Overflow)(value)?,
         b"overflow_clip_margin" => map(parse_overflow_margin, StyleAttr::OverflowClipMargin)(value)?,
 
+        // layering
+        b"z_index" => map(parse_z_index, StyleAttr::ZIndex)(value)?,
+        b"global_z_index" => map(parse_z_index, StyleAttr::GlobalZIndex)(value)?,
+        b"visibility" => map(parse_visibility, StyleAttr::Visibility)(value)?,
+
         // align & justify
         b"align_self" => map(parse_align_self, StyleAttr::AlignSelf)(value)?,
         b"align_items" => map(parse_align_items, StyleAttr::AlignItems)(value)?,
@@ -455,6 +1206,11 @@ where
         //slices
         b"image_mode" => map(parse_image_scale_mode, |v| StyleAttr::ImageScaleMode(v))(value)?,
 
+        // image
+        b"image_color" => map(parse_color, StyleAttr::ImageColor)(value)?,
+        b"flip_x" => map(parse_bool, StyleAttr::FlipX)(value)?,
+        b"flip_y" => map(parse_bool, StyleAttr::FlipY)(value)?,
+
         //shadow
         b"shadow_color" => map(parse_color, StyleAttr::ShadowColor)(value)?,
         b"shadow_offset" => map(tuple((parse_val,preceded(multispace0,parse_val))),|(x,y)| StyleAttr::ShadowOffset(x,y))(value)?,
@@ -470,14 +1226,79 @@ where
         }
     };
 
+    // the shadow copy follows the computed style only
+    if let (Some(prefix), StyleAttr::TextShadow(_)) = (prefix, &style) {
+        let err = E::from_error_kind(prefix, ErrorKind::Verify);
+        return Err(nom::Err::Failure(E::add_context(
+            prefix,
+            "text_shadow cannot be conditional",
+            err,
+        )));
+    }
+
     match prefix {
         Some(b"pressed") => Ok((input, StyleAttr::Pressed(Box::new(style)))),
         Some(b"hover") => Ok((input, StyleAttr::Hover(Box::new(style)))),
         Some(b"active") => Ok((input, StyleAttr::Active(Box::new(style)))),
-        _ => Ok((input, style)),
+        Some(b"focus") => Ok((input, StyleAttr::Focus(Box::new(style)))),
+        Some(b"disabled") => Ok((input, StyleAttr::Disabled(Box::new(style)))),
+        Some(b"enter") => Ok((input, StyleAttr::Enter(Box::new(style)))),
+        Some(b"exit") => Ok((input, StyleAttr::Exit(Box::new(style)))),
+        Some([b'@', query @ ..]) => {
+            let (_, query) = all_consuming(parse_media_query)(query)?;
+            Ok((input, StyleAttr::Media(query, Box::new(style))))
+        }
+        Some(prefix) => match state_name(prefix) {
+            Some(state) => {
+                let state = String::from_utf8_lossy(state).to_string();
+                Ok((input, StyleAttr::State(state, Box::new(style))))
+            }
+            None => Err(unknown_prefix(prefix)),
+        },
+        None => Ok((input, style)),
     }
 }
 
+/// builtin style prefixes, besides media `@..` and `state(..)`
+const STYLE_PREFIXES: [&[u8]; 7] = [
+    b"pressed",
+    b"hover",
+    b"active",
+    b"focus",
+    b"disabled",
+    b"enter",
+    b"exit",
+];
+
+fn is_style_prefix(prefix: &[u8]) -> bool {
+    STYLE_PREFIXES.contains(&prefix) || prefix.starts_with(b"@") || state_name(prefix).is_some()
+}
+
+// `state(checked)` -> `checked`
+fn state_name(prefix: &[u8]) -> Option<&[u8]> {
+    prefix.strip_prefix(b"state(")?.strip_suffix(b")")
+}
+
+fn unknown_prefix<'a, E>(prefix: &'a [u8]) -> nom::Err<E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let err = E::from_error_kind(prefix, ErrorKind::Tag);
+    nom::Err::Failure(E::add_context(
+        prefix,
+        "Unknown style prefix, custom states are written as `state(checked):`",
+        err,
+    ))
+}
+
+// custom state prefix `state(checked)`
+fn take_state_prefix<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    recognize(tuple((tag("state("), take_snake, tag(")"))))(input)
+}
+
 fn parse_float<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], f32, E>
 where
     E: nom::error::ParseError<&'a [u8]>,
@@ -490,6 +1311,7 @@ where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
 {
     match input {
+        b"linear" => Ok((input, EaseFunction::Linear)),
         b"quadratic_in" => Ok((input, EaseFunction::QuadraticIn)),
         b"quadratic_out" => Ok((input, EaseFunction::QuadraticOut)),
         b"quadratic_in_out" => Ok((input, EaseFunction::QuadraticInOut)),
@@ -753,6 +1575,73 @@ where
     )(input)
 }
 
+fn parse_text_justify<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], JustifyText, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "text_justify has no valid value. Try `left` `center` `right` `justified`",
+        alt((
+            map(tag("left"), |_| JustifyText::Left),
+            map(tag("center"), |_| JustifyText::Center),
+            map(tag("right"), |_| JustifyText::Right),
+            map(tag("justified"), |_| JustifyText::Justified),
+        )),
+    )(input)
+}
+
+fn parse_line_break<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], LineBreak, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "line_break has no valid value. Try `word` `char` `word_or_char` `none`",
+        alt((
+            map(tag("word_or_char"), |_| LineBreak::WordOrCharacter),
+            map(tag("word"), |_| LineBreak::WordBoundary),
+            map(tag("char"), |_| LineBreak::AnyCharacter),
+            map(tag("none"), |_| LineBreak::NoWrap),
+        )),
+    )(input)
+}
+
+fn parse_font_smoothing<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], FontSmoothing, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "font_smoothing has no valid value. Try `antialiased` `none`",
+        alt((
+            map(tag("antialiased"), |_| FontSmoothing::AntiAliased),
+            map(tag("none"), |_| FontSmoothing::None),
+        )),
+    )(input)
+}
+
+// 2px 2px #000A
+fn parse_text_shadow<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], TextShadow, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, (x_offset, y_offset, color)) = context(
+        "Is not a valid text_shadow, try `(x) (y) (color)`",
+        tuple((
+            preceded(multispace0, parse_val),
+            preceded(multispace0, parse_val),
+            preceded(multispace0, parse_color),
+        )),
+    )(input)?;
+
+    Ok((
+        input,
+        TextShadow {
+            x_offset,
+            y_offset,
+            color,
+        },
+    ))
+}
+
 fn as_string<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], String, E>
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
@@ -760,27 +1649,70 @@ where
     map(rest, |v| String::from_utf8_lossy(v).to_string())(input)
 }
 
-fn as_string_list<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vec<String>, E>
+// `my_func, buy_item(sword, 3), say('hello, world')`
+fn as_fn_calls<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Vec<FnCall>, E>
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
 {
-    map(
-        separated_list1(tag(","), take_while1(|b: u8| b != b',' && b != b'"')),
-        |bytes: Vec<&[u8]>| {
-            bytes
-                .iter()
-                .map(|b| String::from_utf8_lossy(b).to_string())
-                .collect::<Vec<_>>()
-        },
+    context(
+        "invalid function binding. Try `my_func` or `my_func(arg, 3)`",
+        all_consuming(separated_list1(tag(","), parse_fn_call)),
     )(input)
 }
 
+fn parse_fn_call<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], FnCall, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, name) = delimited(
+        multispace0,
+        take_while1(|b: u8| !b.is_ascii_whitespace() && !b",()\"'".contains(&b)),
+        multispace0,
+    )(input)?;
+
+    let (input, args) = opt(delimited(
+        tag("("),
+        separated_list0(tag(","), parse_fn_arg),
+        preceded(multispace0, tag(")")),
+    ))(input)?;
+
+    let (input, _) = multispace0(input)?;
+
+    Ok((
+        input,
+        FnCall {
+            name: String::from_utf8_lossy(name).to_string(),
+            args: args.unwrap_or_default(),
+        },
+    ))
+}
+
+fn parse_fn_arg<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], String, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    let (input, _) = multispace0(input)?;
+    let (input, arg) = alt((
+        delimited(tag("'"), take_until("'"), tag("'")),
+        map(take_while1(|b: u8| b != b',' && b != b')'), |b: &[u8]| {
+            b.trim_ascii_end()
+        }),
+    ))(input)?;
+    let (input, _) = multispace0(input)?;
+
+    Ok((input, String::from_utf8_lossy(arg).to_string()))
+}
+
 // parse xml prefix
 fn parse_prefix0<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Option<&'a [u8]>, E>
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
 {
-    match terminated(take_snake::<E>, tag(":"))(input) {
+    match terminated(
+        alt((take_media_prefix::<E>, take_state_prefix, take_snake)),
+        tag(":"),
+    )(input)
+    {
         Ok((input, prefix)) => Ok((input, Some(prefix))),
         Err(_) => Ok((input, None)),
     }
@@ -852,6 +1784,30 @@ where
     ))
 }
 
+fn parse_z_index<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], i32, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "Not a valid z_index, try a whole number `-1` `10`",
+        map(parse_number, |i| i32::try_from(i).unwrap_or_default()),
+    )(input)
+}
+
+fn parse_visibility<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], Visibility, E>
+where
+    E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
+{
+    context(
+        "visibility has no valid value. Try `inherited` `visible` `hidden`",
+        alt((
+            map(tag("inherited"), |_| Visibility::Inherited),
+            map(tag("visible"), |_| Visibility::Visible),
+            map(tag("hidden"), |_| Visibility::Hidden),
+        )),
+    )(input)
+}
+
 fn parse_bool<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], bool, E>
 where
     E: ParseError<&'a [u8]> + ContextError<&'a [u8]>,
@@ -1476,6 +2432,7 @@ where
 mod tests {
     use super::*;
     use crate::error::VerboseHtmlError;
+    use crate::{build::TemplateProperties, expr::Expr};
     use nom::error::VerboseError;
     use test_case::test_case;
 
@@ -1632,6 +2589,14 @@ mod tests {
     #[test_case(r#"hover:background="{color}""#)]
     #[test_case(r#"pressed:width="10%""#)]
     #[test_case(r#"active:height="10vw""#)]
+    #[test_case(r#"on_double_click="open(inventory)""#)]
+    #[test_case(r#"on_drag_end="drop_item, reset""#)]
+    #[test_case(r#"focus:border_color="rgb(1,1,1)""#)]
+    #[test_case(r#"tab_index="-1""#)]
+    #[test_case(r#"disabled="true""#)]
+    #[test_case(r#"disabled:background="rgb(1,1,1)""#)]
+    #[test_case(r#"state(checked):border_color="rgb(1,1,1)""#)]
+    #[test_case(r#"enter:left="-100px""#)]
     fn parse_attribute_parts(input: &str) {
         match parse_xml_attr::<nom::error::VerboseError<_>>(input.as_bytes()) {
             Ok((rem, attrs)) => {
@@ -1655,4 +2620,426 @@ mod tests {
         //     max_corner_scale: todo!(),
         // };
     }
+
+    #[test_case("start_game", &[("start_game", &[])]; "plain")]
+    #[test_case("a, b", &[("a", &[]), ("b", &[])]; "list")]
+    #[test_case("buy_item(sword, 3)", &[("buy_item", &["sword", "3"])]; "args")]
+    #[test_case("close(), say('hello, world' )", &[("close", &[]), ("say", &["hello, world"])]; "quoted")]
+    fn test_parse_fn_calls(input: &str, expected: &[(&str, &[&str])]) {
+        let (_, calls) = as_fn_calls::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let calls = calls
+            .iter()
+            .map(|call| {
+                (
+                    call.name.as_str(),
+                    call.args.iter().map(|a| a.as_str()).collect(),
+                )
+            })
+            .collect::<Vec<(&str, Vec<&str>)>>();
+        let expected = expected
+            .iter()
+            .map(|(name, args)| (*name, args.to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(calls, expected);
+    }
+
+    #[test_case("buy_item(sword"; "unclosed")]
+    #[test_case("a,,b"; "empty")]
+    fn test_parse_fn_calls_invalid(input: &str) {
+        assert!(as_fn_calls::<VerboseError<_>>(input.as_bytes()).is_err());
+    }
+
+    #[test_case("item in {items}", "item", "items")]
+    #[test_case("  row   in { rows }", "row", "rows")]
+    fn test_parse_each(input: &str, binding: &str, source: &str) {
+        let (_, each) = parse_each::<VerboseError<_>>(input.as_bytes()).unwrap();
+        assert_eq!(each.binding, binding);
+        assert_eq!(each.source, source);
+    }
+
+    #[test]
+    fn test_parse_for_node() {
+        let input = r#"<template><for each="item in {items}"><text>{item}</text></for></template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let node = template.root.first().unwrap();
+        assert!(matches!(node.node_type, NodeType::For));
+        assert_eq!(node.children.len(), 1);
+        assert_eq!(
+            node.each,
+            Some(ForEach {
+                binding: "item".into(),
+                source: "items".into()
+            })
+        );
+    }
+
+    #[test_case("{visible}", true)]
+    #[test_case("{ !visible }", false)]
+    #[test_case("{mode == 'dark'}", true)]
+    #[test_case("{count != 3}", false)]
+    fn test_parse_condition(input: &str, expected: bool) {
+        let (_, condition) =
+            all_consuming(parse_placeholder::<VerboseError<_>>)(input.as_bytes()).unwrap();
+        let props = TemplateProperties::default()
+            .with("visible", "true")
+            .with("mode", "dark")
+            .with("count", "3");
+        let value = condition.eval(&props).unwrap();
+        assert_eq!(value.is_truthy(), expected);
+    }
+
+    #[test_case(r#"<text>{hp + }</text>"#)]
+    #[test_case(r#"<node width="{hp *}%"/>"#)]
+    #[test_case(r#"<node if="{a ==}"/>"#)]
+    fn test_invalid_expression(input: &str) {
+        let input = format!("<template>{input}</template>");
+        let result = parse_template::<VerboseHtmlError>(input.as_bytes());
+        assert!(matches!(result, Err(nom::Err::Failure(_))));
+    }
+
+    #[test_case(r#"<text>Press { to open</text>"#)]
+    #[test_case(r#"<text>{{hp} is {hp}</text>"#)]
+    #[test_case(r#"<image src="icons/{name.png"/>"#)]
+    fn test_literal_bracket(input: &str) {
+        let input = format!("<template>{input}</template>");
+        let result = parse_template::<VerboseHtmlError>(input.as_bytes());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_parse_branch_chain() {
+        let input = r#"<template><node><text if="{a}">a</text><text else_if="{b}">b</text><else><text>c</text></else></node></template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let node = template.root.first().unwrap();
+        assert_eq!(
+            node.children[0].branch,
+            Some(Branch::If(Expr::Key("a".into())))
+        );
+        assert_eq!(
+            node.children[1].branch,
+            Some(Branch::ElseIf(Expr::Key("b".into())))
+        );
+        assert_eq!(node.children[2].branch, Some(Branch::Else));
+        assert!(matches!(node.children[2].node_type, NodeType::Node));
+    }
+
+    #[test]
+    fn test_parse_named_slot() {
+        let input = r#"<template><node><slot name="footer"><text>empty</text></slot><slot/><text slot="footer">ok</text></node></template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let node = template.root.first().unwrap();
+        assert!(matches!(node.children[0].node_type, NodeType::Slot));
+        assert_eq!(node.children[0].name.as_deref(), Some("footer"));
+        assert_eq!(node.children[0].children.len(), 1);
+        assert_eq!(node.children[1].name, None);
+        assert_eq!(node.children[2].slot.as_deref(), Some("footer"));
+    }
+
+    #[test]
+    fn test_parse_fragment() {
+        let input = r#"<template><property name="a">1</property><button/><button/><text>c</text></template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        assert_eq!(template.root.len(), 3);
+        assert!(matches!(template.root[2].node_type, NodeType::Text));
+    }
+
+    #[test]
+    fn test_parse_stylesheet() {
+        let input = r#"
+            /* cards */
+            .card, button.primary:hover {
+                background: #FFF;
+                hover:padding: 5px;
+            }
+            #title { font_size: 20 }
+        "#;
+        let (_, sheet) = parse_stylesheet::<VerboseError<_>>(input.as_bytes()).unwrap();
+        assert_eq!(sheet.rules.len(), 3);
+        assert_eq!(sheet.rules[0].selector.classes, vec!["card".to_string()]);
+        assert_eq!(sheet.rules[1].selector.tag.as_deref(), Some("button"));
+        assert!(matches!(sheet.rules[0].styles[0], StyleAttr::Background(_)));
+        assert!(matches!(sheet.rules[0].styles[1], StyleAttr::Hover(_)));
+        assert!(matches!(sheet.rules[1].styles[0], StyleAttr::Hover(_)));
+        assert_eq!(sheet.rules[2].selector.id.as_deref(), Some("title"));
+    }
+
+    #[test]
+    fn test_parse_rich_text() {
+        let input = r##"<template>
+            <text font_size="20">
+                Deals <span font_color="#F00" hover:font_color="#FFF">{damage}</span>
+                <!-- unit -->
+                damage
+            </text>
+        </template>"##;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let text = &template.root[0];
+        assert!(template.content.get(text.content_id).is_none());
+
+        let content = |node: &XNode| template.content.get(node.content_id).unwrap().clone();
+        let spans = &text.children;
+        assert_eq!(spans.len(), 4);
+        assert!(spans.iter().all(|s| matches!(s.node_type, NodeType::Span)));
+        assert_eq!(content(&spans[0]), "Deals ");
+        assert_eq!(content(&spans[1]), "{damage}");
+        assert_eq!(spans[1].styles.len(), 2);
+        assert_eq!(content(&spans[2]), " ");
+        assert_eq!(content(&spans[3]), "damage");
+    }
+
+    #[test]
+    fn test_parse_plain_text() {
+        let input = r#"<template><text>  Hello {name}  </text></template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let text = &template.root[0];
+        assert!(text.children.is_empty());
+        assert_eq!(
+            template.content.get(text.content_id).unwrap(),
+            "  Hello {name}  "
+        );
+    }
+
+    #[test]
+    fn test_parse_enter_exit() {
+        let input =
+            r#"<template><node enter:left="-100px" exit:background="rgba(0,0,0,0)"/></template>"#;
+        let (_, node) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let styles = &node.root[0].styles;
+        assert!(
+            matches!(&styles[0], StyleAttr::Enter(style) if matches!(**style, StyleAttr::Left(_)))
+        );
+        assert!(
+            matches!(&styles[1], StyleAttr::Exit(style) if matches!(**style, StyleAttr::Background(_)))
+        );
+    }
+
+    #[test]
+    fn test_parse_custom_state() {
+        let input = ".toggle:state(checked) { background: #FFF; state(error):border_color: #F00; }";
+        let (_, sheet) = parse_stylesheet::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let styles = &sheet.rules[0].styles;
+        assert!(matches!(&styles[0], StyleAttr::State(state, _) if state == "checked"));
+        assert!(matches!(&styles[1], StyleAttr::State(state, _) if state == "error"));
+    }
+
+    #[test_case(r##"<template><node hovr:background="#FFF"/></template>"##)]
+    #[test_case(r##"<template><node hovr:background="{color}"/></template>"##)]
+    #[test_case(r##"<template><card hovr:background="#FFF"/></template>"##)]
+    #[test_case(r##"<template><style>.card:hovr { background: #FFF; }</style><node/></template>"##)]
+    #[test_case(
+        r##"<template><style>.card { checked:background: #FFF; }</style><node/></template>"##
+    )]
+    fn test_reject_unknown_prefix(input: &str) {
+        assert!(parse_template::<VerboseError<_>>(input.as_bytes()).is_err());
+    }
+
+    #[test_case("hover")]
+    #[test_case("state(checked)")]
+    #[test_case("@sm")]
+    fn test_reject_conditional_text_shadow(prefix: &str) {
+        let result = parse_style::<VerboseError<&[u8]>>(
+            Some(prefix.as_bytes()),
+            b"text_shadow",
+            b"2px 2px #000",
+        );
+        assert!(matches!(result, Err(nom::Err::Failure(_))));
+    }
+
+    #[test_case("w<800", MediaQuery::Width(MediaCompare::Less, 800.))]
+    #[test_case("h>=600", MediaQuery::Height(MediaCompare::GreaterEqual, 600.))]
+    #[test_case("w>1024.5", MediaQuery::Width(MediaCompare::Greater, 1024.5))]
+    #[test_case("sm", MediaQuery::Breakpoint("sm".into()))]
+    fn test_parse_media_query(input: &str, expected: MediaQuery) {
+        let (_, query) = parse_media_query::<VerboseError<&[u8]>>(input.as_bytes()).unwrap();
+        assert_eq!(query, expected);
+    }
+
+    #[test_case("text_justify", "justified" => matches StyleAttr::TextJustify(JustifyText::Justified))]
+    #[test_case("line_break", "word" => matches StyleAttr::LineBreak(LineBreak::WordBoundary))]
+    #[test_case("line_break", "word_or_char" => matches StyleAttr::LineBreak(LineBreak::WordOrCharacter))]
+    #[test_case("line_break", "none" => matches StyleAttr::LineBreak(LineBreak::NoWrap))]
+    #[test_case("font_smoothing", "none" => matches StyleAttr::FontSmoothing(FontSmoothing::None))]
+    fn test_parse_text_layout(ident: &str, value: &str) -> StyleAttr {
+        let (_, style) =
+            parse_style::<VerboseError<&[u8]>>(None, ident.as_bytes(), value.as_bytes()).unwrap();
+        style
+    }
+
+    #[test_case("z_index", "-3" => matches StyleAttr::ZIndex(-3))]
+    #[test_case("global_z_index", "100" => matches StyleAttr::GlobalZIndex(100))]
+    #[test_case("visibility", "hidden" => matches StyleAttr::Visibility(Visibility::Hidden))]
+    #[test_case("aspect_ratio", "1.5" => matches StyleAttr::AspectRatio(r) if r == 1.5)]
+    #[test_case("flip_x", "true" => matches StyleAttr::FlipX(true))]
+    #[test_case("image_color", "#F00" => matches StyleAttr::ImageColor(_))]
+    fn test_parse_node_layering(ident: &str, value: &str) -> StyleAttr {
+        let (_, style) =
+            parse_style::<VerboseError<&[u8]>>(None, ident.as_bytes(), value.as_bytes()).unwrap();
+        style
+    }
+
+    #[test]
+    fn test_parse_atlas() {
+        let input = r#"<template><image src="icons.png" atlas_layout="tile(32,32) cols(8) rows(4) padding(2, 2)" atlas_frames="0..7" fps="12"/><image atlas_index="{icon}"/></template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+
+        let spinner = &template.root[0];
+        assert_eq!(
+            spinner.atlas_layout,
+            Some(AtlasLayout {
+                tile_size: UVec2::new(32, 32),
+                columns: 8,
+                rows: 4,
+                padding: Some(UVec2::new(2, 2)),
+                offset: None,
+            })
+        );
+        assert_eq!(
+            spinner.atlas_frames,
+            Some(AtlasFrames { first: 0, last: 7 })
+        );
+        assert_eq!(spinner.fps, Some(12.));
+
+        let icon = &template.root[1];
+        assert!(icon.atlas_index.is_none());
+        assert_eq!(icon.uncompiled.len(), 1);
+        assert_eq!(icon.uncompiled[0].ident, "atlas_index");
+        assert_eq!(icon.uncompiled[0].raw, "{icon}");
+
+        assert!(parse_atlas_layout::<VerboseError<&[u8]>>(b"cols(8) rows(4)").is_err());
+        assert!(parse_atlas_layout::<VerboseError<&[u8]>>(b"tile(32) cols(8)").is_err());
+    }
+
+    #[test]
+    fn test_parse_text_shadow() {
+        let (_, shadow) = parse_text_shadow::<VerboseError<&[u8]>>(b"2px -1px #000").unwrap();
+        assert_eq!(
+            shadow,
+            TextShadow {
+                x_offset: Val::Px(2.),
+                y_offset: Val::Px(-1.),
+                color: Color::BLACK,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_media_prefix() {
+        let input =
+            r#"<template><node @w<800:flex_direction="column" @lg:width="50%"/></template>"#;
+        let (_, node) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let styles = &node.root[0].styles;
+        assert!(matches!(
+            &styles[0],
+            StyleAttr::Media(MediaQuery::Width(MediaCompare::Less, w), style)
+                if *w == 800. && matches!(**style, StyleAttr::FlexDirection(_))
+        ));
+        assert!(matches!(
+            &styles[1],
+            StyleAttr::Media(MediaQuery::Breakpoint(name), _) if name == "lg"
+        ));
+
+        let input = ".panel { @h<=600:height: 100%; }";
+        let (_, sheet) = parse_stylesheet::<VerboseError<_>>(input.as_bytes()).unwrap();
+        assert!(matches!(
+            &sheet.rules[0].styles[0],
+            StyleAttr::Media(MediaQuery::Height(MediaCompare::LessEqual, _), _)
+        ));
+    }
+
+    #[test]
+    fn test_parse_keyframes() {
+        let input = r#"<template>
+            <keyframes name="pulse">
+                /* grow */
+                50% { width: 20px; background: rgb(1,1,1); }
+                from { width: 10px; }
+                to { width: 10px; }
+            </keyframes>
+            <node animation="pulse 300ms infinite alternate sine_in_out"/>
+        </template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let keyframes = template.keyframes.get("pulse").unwrap();
+        assert_eq!(keyframes.stops.len(), 3);
+        assert_eq!(keyframes.stops[1].at, 0.5);
+
+        let sampled = keyframes.sample(0.25);
+        assert!(
+            matches!(sampled[0], (Some(StyleAttr::Width(_)), StyleAttr::Width(_), r) if r == 0.5)
+        );
+        assert!(matches!(sampled[1], (None, StyleAttr::Background(_), r) if r == 0.5));
+
+        let animation = template.root[0].animation.as_ref().unwrap();
+        assert_eq!(animation.name, "pulse");
+        assert_eq!(animation.duration, 0.3);
+        assert_eq!(animation.repeat, AnimationRepeat::Infinite);
+        assert!(animation.alternate);
+        assert_eq!(animation.easing, Some(EaseFunction::SineInOut));
+    }
+
+    #[test]
+    fn test_parse_transitions() {
+        let input = b"background 100ms linear, width 0.2/400ms cubic_out ,height 1s";
+        let (_, transitions) = parse_transitions::<VerboseError<_>>(input).unwrap();
+        assert_eq!(transitions.len(), 3);
+        assert_eq!(transitions[0].property, "background");
+        assert_eq!(transitions[0].easing, Some(EaseFunction::Linear));
+        assert_eq!(transitions[1].duration, 0.2);
+        assert_eq!(transitions[1].duration_out, Some(0.4));
+        assert_eq!(transitions[2].duration, 1.);
+        assert_eq!(transitions[2].easing, None);
+    }
+
+    #[test_case("background")]
+    #[test_case("background 1s wobble")]
+    #[test_case("width 1s, ")]
+    fn test_parse_transitions_invalid(input: &str) {
+        assert!(parse_transitions::<VerboseError<_>>(input.as_bytes()).is_err());
+    }
+
+    #[test_case("pulse 1s forever")]
+    #[test_case("1s")]
+    #[test_case("pulse 1x")]
+    fn test_parse_animation_invalid(input: &str) {
+        assert!(parse_animation::<VerboseError<_>>(input.as_bytes()).is_err());
+    }
+
+    #[test_case(".card { background: #FFF ")]
+    #[test_case(".card { color: #FFF; }")]
+    #[test_case("{ background: #FFF; }")]
+    fn test_parse_stylesheet_invalid(input: &str) {
+        assert!(parse_stylesheet::<VerboseError<_>>(input.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_style_matching() {
+        let input = r#"<template>
+            <style>
+                #save { background: #F00; }
+                .btn { background: #0F0; width: 10px; }
+                button { background: #00F; height: 5px; }
+            </style>
+            <button id="save" class="btn large" height="20px"/>
+        </template>"#;
+        let (_, template) = parse_template::<VerboseError<_>>(input.as_bytes()).unwrap();
+        let node = template.root.first().unwrap();
+        let mut styles = template.matching_styles(node, &Default::default());
+        styles.extend(node.styles.iter().cloned());
+        let style = crate::styles::HtmlStyle::from(styles);
+        assert_eq!(style.computed.background, Color::linear_rgb(1., 0., 0.));
+        assert_eq!(style.computed.node.width, Val::Px(10.));
+        assert_eq!(style.computed.node.height, Val::Px(20.));
+    }
+
+    #[test_case("{volume}", "volume")]
+    #[test_case("{ res:Settings.volume }", "res:Settings.volume")]
+    fn test_parse_bind(input: &str, expected: &str) {
+        let (_, bind) = parse_bind::<VerboseError<_>>(input.as_bytes()).unwrap();
+        assert_eq!(bind, expected);
+    }
+
+    #[test_case("{a + b}")]
+    #[test_case("volume")]
+    fn test_parse_bind_invalid(input: &str) {
+        assert!(parse_bind::<VerboseError<_>>(input.as_bytes()).is_err());
+    }
 }
@@ -0,0 +1,517 @@
+use crate::{
+    build::{ContentId, HtmlNode, TemplateExpresions, TemplateProperties, TemplateScope, UiBranch},
+    compile::{CompileContentEvent, CompileContextEvent, CompileNodeEvent},
+    data::{HtmlTemplate, NodeType, XNode},
+    expr::{parse_content, Expr},
+};
+use bevy::{
+    ecs::{component::Tick, system::EntityCommands},
+    prelude::*,
+    reflect::{PartialReflect, ReflectPath},
+};
+
+pub struct ReflectBindingPlugin;
+impl Plugin for ReflectBindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, sync_reflect_bindings);
+    }
+}
+
+/// Property keys of a node, that are read from
+/// reflected resources and components.
+///
+/// `{res:PlayerStats.gold}` reads the field `gold` of the resource `PlayerStats`.
+/// `{comp:Health.current}` reads the component `Health` of the template entity.
+///
+/// Supported values are `String`, `char`, `bool` and the number
+/// primitives. Other types are not bound and log a warning.
+#[derive(Component, Debug, Default)]
+pub struct ReflectBindings {
+    keys: Vec<String>,
+    last_sync: Option<Tick>,
+}
+
+impl ReflectBindings {
+    pub fn new(keys: Vec<String>) -> Self {
+        Self {
+            keys,
+            last_sync: None,
+        }
+    }
+
+    /// adds the keys to an existing binding,
+    /// branches build into their placeholder.
+    pub(crate) fn bind(mut entity: EntityCommands, keys: Vec<String>) {
+        if keys.is_empty() {
+            return;
+        }
+
+        entity.queue(
+            move |mut entity: EntityWorldMut| match entity.get_mut::<Self>() {
+                Some(mut bindings) => {
+                    for key in keys {
+                        if !bindings.keys.contains(&key) {
+                            bindings.keys.push(key);
+                        }
+                    }
+                    bindings.last_sync = None;
+                }
+                None => {
+                    entity.insert(Self::new(keys));
+                }
+            },
+        );
+    }
+}
+
+enum ReflectSource<'a> {
+    Resource { ty: &'a str, path: &'a str },
+    Component { ty: &'a str, path: &'a str },
+}
+
+impl<'a> ReflectSource<'a> {
+    fn parse(key: &'a str) -> Option<Self> {
+        let split = |rest: &'a str| rest.split_once('.').unwrap_or((rest, ""));
+        if let Some(rest) = key.strip_prefix("res:") {
+            let (ty, path) = split(rest);
+            return Some(Self::Resource { ty, path });
+        }
+        if let Some(rest) = key.strip_prefix("comp:") {
+            let (ty, path) = split(rest);
+            return Some(Self::Component { ty, path });
+        }
+        None
+    }
+}
+
+pub(crate) fn is_reflected(key: &str) -> bool {
+    key.starts_with("res:") || key.starts_with("comp:")
+}
+
+/// reflected keys used by a node's attributes and text
+pub(crate) fn reflected_keys(node: &XNode, template: &HtmlTemplate) -> Vec<String> {
+    let mut keys = node
+        .uncompiled
+        .iter()
+        .flat_map(|tokens| tokens.value.keys())
+        .filter(|key| is_reflected(key))
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    if let NodeType::Text | NodeType::Span = node.node_type {
+        if let Some(interpolation) = template
+            .content
+            .get(node.content_id)
+            .and_then(|content| parse_content(content.trim()))
+        {
+            keys.extend(
+                interpolation
+                    .keys()
+                    .into_iter()
+                    .filter(|key| is_reflected(key))
+                    .map(String::from),
+            );
+        }
+    }
+
+    keys
+}
+
+/// reflected keys used by branch conditions
+pub(crate) fn reflected_expr_keys<'a>(exprs: impl Iterator<Item = &'a Expr>) -> Vec<String> {
+    exprs
+        .flat_map(|expr| expr.keys())
+        .filter(|key| is_reflected(key))
+        .map(String::from)
+        .collect()
+}
+
+/// copies reflected values into the template properties,
+/// when the source changed since the last sync and
+/// recompiles the node.
+pub(crate) fn sync_reflect_bindings(world: &mut World) {
+    sync_bindings(world, false);
+}
+
+/// resolves the bindings, that never synced yet.
+pub(crate) fn sync_new_reflect_bindings(world: &mut World) {
+    sync_bindings(world, true);
+}
+
+fn sync_bindings(world: &mut World, only_new: bool) {
+    let this_run = world.change_tick();
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    // only read the values of sources, that changed
+    let mut query = world.query::<(Entity, &ReflectBindings)>();
+    let world_ref = &*world;
+    let bindings = query
+        .iter(world_ref)
+        .filter(|(entity, bindings)| match bindings.last_sync {
+            None => true,
+            Some(_) if only_new => false,
+            Some(last) => {
+                let owner = template_entity(world_ref, *entity);
+                bindings.keys.iter().any(|key| {
+                    ReflectSource::parse(key)
+                        .and_then(|source| source_ticks(world_ref, &registry, owner, &source))
+                        .is_some_and(|ticks| ticks.is_changed(last, this_run))
+                })
+            }
+        })
+        .map(|(entity, bindings)| (entity, bindings.keys.clone()))
+        .collect::<Vec<_>>();
+
+    for (entity, keys) in bindings {
+        let owner = template_entity(world, entity);
+        let values = keys
+            .iter()
+            .filter_map(|key| {
+                let source = ReflectSource::parse(key)?;
+                let value = read_source(world, &registry, owner, &source)?;
+                Some((key.clone(), value))
+            })
+            .collect::<Vec<_>>();
+
+        // custom nodes compile their attributes from the
+        // outer scope, but own their properties.
+        let scope = world.get::<TemplateScope>(entity).map(|scope| **scope);
+        for context in std::iter::once(entity).chain(scope) {
+            if let Some(mut props) = world.get_mut::<TemplateProperties>(context) {
+                props.extend(values.iter().cloned());
+            }
+        }
+
+        if let Some(mut bindings) = world.get_mut::<ReflectBindings>(entity) {
+            bindings.last_sync = Some(this_run);
+        }
+
+        if world.get::<TemplateExpresions>(entity).is_some() {
+            world.trigger_targets(CompileNodeEvent, entity);
+        }
+
+        if world.get::<ContentId>(entity).is_some() {
+            world.trigger_targets(CompileContentEvent, entity);
+        }
+
+        // custom nodes pass the new value on to their own subscribers
+        if world.get::<UiBranch>(entity).is_some()
+            || world.get::<TemplateProperties>(entity).is_some()
+        {
+            world.trigger_targets(CompileContextEvent, entity);
+        }
+    }
+}
+
+/// writes a value back into a reflected resource or component,
+/// parsed into the type of the target field.
+pub(crate) fn write_reflected(world: &mut World, entity: Entity, key: &str, value: &str) {
+    let Some(source) = ReflectSource::parse(key) else {
+        return;
+    };
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    let (ty, path) = match source {
+        ReflectSource::Resource { ty, path } | ReflectSource::Component { ty, path } => (ty, path),
+    };
+
+    let Some(registration) = registration(&registry, ty) else {
+        warn!("cannot bind to `{key}`, type `{ty}` is not registered");
+        return;
+    };
+
+    let written = match source {
+        ReflectSource::Resource { .. } => registration
+            .data::<ReflectResource>()
+            .and_then(|reflect| reflect.reflect_mut(world))
+            .and_then(|mut target| write_path(target.as_partial_reflect_mut(), path, value)),
+        ReflectSource::Component { .. } => {
+            let owner = template_entity(world, entity);
+            let Ok(mut owner) = world.get_entity_mut(owner) else {
+                return;
+            };
+            registration
+                .data::<ReflectComponent>()
+                .and_then(|reflect| reflect.reflect_mut(&mut owner))
+                .and_then(|mut target| write_path(target.as_partial_reflect_mut(), path, value))
+        }
+    };
+
+    if written.is_none() {
+        warn!("failed to write `{value}` to `{key}`");
+    }
+}
+
+fn write_path(target: &mut dyn PartialReflect, path: &str, value: &str) -> Option<()> {
+    let target = match path.is_empty() {
+        true => target,
+        false => path.reflect_element_mut(target).ok()?,
+    };
+
+    macro_rules! try_parse {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(field) = target.try_downcast_mut::<$ty>() {
+                    *field = value.trim().parse::<$ty>().ok()?;
+                    return Some(());
+                }
+            )*
+        };
+    }
+
+    if let Some(field) = target.try_downcast_mut::<String>() {
+        *field = value.to_string();
+        return Some(());
+    }
+
+    try_parse!(char, bool, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+    None
+}
+
+/// the spawned template entity, owning the
+/// components for `comp:` bindings
+fn template_entity(world: &World, entity: Entity) -> Entity {
+    let mut current = entity;
+    loop {
+        if world.get::<HtmlNode>(current).is_some() {
+            return current;
+        }
+        match world.get::<TemplateScope>(current) {
+            Some(scope) => current = **scope,
+            None => return current,
+        }
+    }
+}
+
+fn registration<'a>(
+    registry: &'a bevy::reflect::TypeRegistry,
+    ty: &str,
+) -> Option<&'a bevy::reflect::TypeRegistration> {
+    registry
+        .get_with_short_type_path(ty)
+        .or_else(|| registry.get_with_type_path(ty))
+}
+
+fn source_ticks(
+    world: &World,
+    registry: &bevy::reflect::TypeRegistry,
+    owner: Entity,
+    source: &ReflectSource,
+) -> Option<bevy::ecs::component::ComponentTicks> {
+    match source {
+        ReflectSource::Resource { ty, .. } => {
+            let registration = registration(registry, ty)?;
+            let id = world.components().get_resource_id(registration.type_id())?;
+            world.get_resource_change_ticks_by_id(id)
+        }
+        ReflectSource::Component { ty, .. } => {
+            let registration = registration(registry, ty)?;
+            let id = world.components().get_id(registration.type_id())?;
+            world.get_entity(owner).ok()?.get_change_ticks_by_id(id)
+        }
+    }
+}
+
+fn read_source(
+    world: &World,
+    registry: &bevy::reflect::TypeRegistry,
+    owner: Entity,
+    source: &ReflectSource,
+) -> Option<String> {
+    match source {
+        ReflectSource::Resource { ty, path } => {
+            let reflect = registration(registry, ty)?.data::<ReflectResource>()?;
+            let value = reflect.reflect(world)?;
+            read_path(value.as_partial_reflect(), path)
+        }
+        ReflectSource::Component { ty, path } => {
+            let reflect = registration(registry, ty)?.data::<ReflectComponent>()?;
+            let value = reflect.reflect(world.get_entity(owner).ok()?)?;
+            read_path(value.as_partial_reflect(), path)
+        }
+    }
+}
+
+fn read_path(value: &dyn PartialReflect, path: &str) -> Option<String> {
+    let value = match path.is_empty() {
+        true => value,
+        false => path.reflect_element(value).ok()?,
+    };
+
+    macro_rules! try_display {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(v) = value.try_downcast_ref::<$ty>() {
+                    return Some(v.to_string());
+                }
+            )*
+        };
+    }
+
+    try_display!(String, char, bool, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+    warn!(
+        "cannot bind `{path}` of type `{}`, only strings, chars, bools and numbers are supported",
+        value.reflect_type_path()
+    );
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        add_template, bindings::ComponentBindings, build::HtmlNode, compile::CompileContentEvent,
+        spawn_template, test_app,
+    };
+    use bevy::prelude::*;
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct PlayerStats {
+        gold: u32,
+        position: Vec2,
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Health {
+        current: u32,
+    }
+
+    #[derive(Resource, Default)]
+    struct Compiles(usize);
+
+    fn text(app: &mut App) -> Vec<String> {
+        app.world_mut()
+            .query::<&Text>()
+            .iter(app.world())
+            .map(|text| text.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_update_reflected_resource() {
+        let mut app = test_app();
+        app.register_type::<PlayerStats>()
+            .init_resource::<PlayerStats>();
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><text>Gold: {res:PlayerStats.gold}</text></node></template>"#,
+        );
+
+        app.update();
+        app.update();
+        assert_eq!(text(&mut app), ["Gold: 0"]);
+
+        app.world_mut().resource_mut::<PlayerStats>().gold = 42;
+        app.update();
+        assert_eq!(text(&mut app), ["Gold: 42"]);
+    }
+
+    #[test]
+    fn test_update_reflected_attribute() {
+        let mut app = test_app();
+        app.register_type::<PlayerStats>()
+            .init_resource::<PlayerStats>();
+
+        let card = add_template(
+            &mut app,
+            r#"<template><property name="title"></property><node><text>T {title}</text></node></template>"#,
+        );
+        app.world_mut()
+            .resource_mut::<ComponentBindings>()
+            .register("card", move |mut cmd| {
+                cmd.insert(HtmlNode(card.clone()));
+            });
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><card title="{res:PlayerStats.gold}"/></node></template>"#,
+        );
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(text(&mut app), ["T 0"]);
+
+        app.world_mut().resource_mut::<PlayerStats>().gold = 42;
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(text(&mut app), ["T 42"]);
+    }
+
+    #[test]
+    fn test_update_reflected_component() {
+        let mut app = test_app();
+        app.register_type::<Health>();
+
+        let root = spawn_template(
+            &mut app,
+            r#"<template><node><text>HP: {comp:Health.current}</text></node></template>"#,
+        );
+        app.world_mut()
+            .entity_mut(root)
+            .insert(Health { current: 7 });
+
+        app.update();
+        app.update();
+        assert_eq!(text(&mut app), ["HP: 7"]);
+
+        app.world_mut().get_mut::<Health>(root).unwrap().current = 3;
+        app.update();
+        assert_eq!(text(&mut app), ["HP: 3"]);
+    }
+
+    #[test]
+    fn test_skip_unchanged_sources() {
+        let mut app = test_app();
+        app.register_type::<PlayerStats>()
+            .init_resource::<PlayerStats>()
+            .init_resource::<Compiles>()
+            .add_observer(
+                |_: Trigger<CompileContentEvent>, mut compiles: ResMut<Compiles>| {
+                    compiles.0 += 1;
+                },
+            );
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><text>Gold: {res:PlayerStats.gold}</text></node></template>"#,
+        );
+
+        app.update();
+        app.update();
+        let compiles = app.world().resource::<Compiles>().0;
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Compiles>().0, compiles);
+
+        app.world_mut().resource_mut::<PlayerStats>().gold = 5;
+        app.update();
+        assert_eq!(app.world().resource::<Compiles>().0, compiles + 1);
+        assert_eq!(text(&mut app), ["Gold: 5"]);
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        let mut app = test_app();
+        app.register_type::<PlayerStats>()
+            .init_resource::<PlayerStats>();
+
+        spawn_template(
+            &mut app,
+            r#"<template><node><text>At: {res:PlayerStats.position}</text></node></template>"#,
+        );
+
+        app.update();
+        app.update();
+        assert_eq!(text(&mut app), ["At: "]);
+    }
+}
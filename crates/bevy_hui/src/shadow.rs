@@ -0,0 +1,465 @@
+use crate::styles::HtmlStyle;
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    transform::TransformSystem,
+    ui::{FocusPolicy, UiSystem},
+};
+
+pub struct TextShadowPlugin;
+impl Plugin for TextShadowPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<UiTextShadow>()
+            .register_type::<TextShadowOf>()
+            .add_observer(despawn_shadow)
+            .add_systems(Update, spawn_text_shadows)
+            .add_systems(
+                PostUpdate,
+                (
+                    sync_text_shadows.before(UiSystem::Prepare),
+                    place_text_shadows
+                        .after(UiSystem::Layout)
+                        .before(TransformSystem::TransformPropagate),
+                ),
+            );
+    }
+}
+
+/// points to the shadow copy of a text with a `text_shadow` style.
+///
+/// The shadow is an absolute positioned sibling, appended to
+/// the parent with a lower `ZIndex`. As a child it would turn
+/// the text into a layout container and lose its measured size.
+/// It is moved onto the text right after layout.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect]
+pub struct UiTextShadow(pub Entity);
+
+/// marks the shadow copy of a text
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect]
+pub struct TextShadowOf(pub Entity);
+
+fn despawn_shadow(
+    trigger: Trigger<OnRemove, UiTextShadow>,
+    shadows: Query<&UiTextShadow>,
+    mut cmd: Commands,
+) {
+    let Ok(shadow) = shadows.get(trigger.entity()) else {
+        return;
+    };
+
+    if let Some(cmd) = cmd.get_entity(shadow.0) {
+        cmd.try_despawn_recursive();
+    }
+}
+
+type StyledText<'a> = (
+    Entity,
+    &'a HtmlStyle,
+    Option<&'a UiTextShadow>,
+    Option<&'a Parent>,
+);
+
+type ShadowChanged = (With<Text>, Or<(Changed<HtmlStyle>, Changed<Parent>)>);
+
+fn spawn_text_shadows(
+    mut cmd: Commands,
+    texts: Query<StyledText, ShadowChanged>,
+    shadows: Query<Option<&Parent>, With<TextShadowOf>>,
+) {
+    for (entity, style, shadow, parent) in texts.iter() {
+        if style.computed.text_shadow.is_none() {
+            if shadow.is_some() {
+                cmd.entity(entity).remove::<UiTextShadow>();
+            }
+            continue;
+        }
+
+        let Some(parent) = parent.map(|parent| parent.get()) else {
+            warn!("text_shadow on the root text {entity} is not supported, wrap it in a node");
+            if shadow.is_some() {
+                cmd.entity(entity).remove::<UiTextShadow>();
+            }
+            continue;
+        };
+
+        let current = shadow.and_then(|shadow| {
+            shadows
+                .get(shadow.0)
+                .ok()
+                .map(|shadow_parent| (shadow.0, shadow_parent.map(|p| p.get())))
+        });
+
+        match current {
+            // the text moved, slots and fragments reparent it
+            Some((shadow, shadow_parent)) => {
+                if shadow_parent != Some(parent) {
+                    cmd.entity(parent).add_child(shadow);
+                }
+            }
+            // new, or despawned with the old parent of the text
+            None => {
+                let shadow = cmd
+                    .spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            ..default()
+                        },
+                        Text::default(),
+                        TextShadowOf(entity),
+                        PickingBehavior::IGNORE,
+                        FocusPolicy::Pass,
+                    ))
+                    .id();
+
+                cmd.entity(parent).add_child(shadow);
+                cmd.entity(entity).insert(UiTextShadow(shadow));
+            }
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct ShadowCopy<'w, 's> {
+    nodes: Query<
+        'w,
+        's,
+        (
+            &'static mut Node,
+            &'static mut ZIndex,
+            &'static mut Visibility,
+        ),
+    >,
+    texts: Query<
+        'w,
+        's,
+        (
+            &'static mut Text,
+            &'static mut TextFont,
+            &'static mut TextLayout,
+        ),
+    >,
+    spans: Query<'w, 's, (&'static mut TextSpan, &'static mut TextFont), Without<Text>>,
+    colors: Query<'w, 's, &'static mut TextColor>,
+    children: Query<'w, 's, &'static Children>,
+}
+
+fn sync_text_shadows(
+    mut cmd: Commands,
+    texts: Query<(Entity, &HtmlStyle, &UiTextShadow, Option<&ComputedNode>)>,
+    mut copy: ShadowCopy,
+) {
+    for (entity, style, shadow, computed) in texts.iter() {
+        let Some(text_shadow) = style.computed.text_shadow else {
+            continue;
+        };
+
+        copy.copy_text(entity, shadow.0);
+        copy.copy_spans(&mut cmd, entity, shadow.0, text_shadow.color);
+        copy.set_color(shadow.0, text_shadow.color);
+
+        // wraps at the width of the last text layout
+        let width = computed
+            .map(|node| Val::Px(node.size().x * node.inverse_scale_factor()))
+            .unwrap_or_default();
+
+        let Ok(
+            [(node, z_index, visibility), (mut shadow_node, mut shadow_z, mut shadow_visibility)],
+        ) = copy.nodes.get_many_mut([entity, shadow.0])
+        else {
+            continue;
+        };
+
+        if shadow_node.display != node.display || shadow_node.width != width {
+            shadow_node.display = node.display;
+            shadow_node.width = width;
+        }
+
+        shadow_z.set_if_neq(ZIndex(z_index.0 - 1));
+        shadow_visibility.set_if_neq(*visibility);
+    }
+}
+
+/// moves the shadows onto their text, shifted by the offset.
+/// Layout resets the translation each frame.
+fn place_text_shadows(
+    texts: Query<(&HtmlStyle, &UiTextShadow, &Transform, &ComputedNode), Without<TextShadowOf>>,
+    mut shadows: Query<&mut Transform, With<TextShadowOf>>,
+) {
+    for (style, shadow, transform, node) in texts.iter() {
+        let Some(text_shadow) = style.computed.text_shadow else {
+            continue;
+        };
+
+        let Ok(mut shadow_transform) = shadows.get_mut(shadow.0) else {
+            continue;
+        };
+
+        // transforms are in physical pixels
+        let scale = node.inverse_scale_factor().recip();
+        let resolve = |val: Val, size: f32| match val {
+            Val::Px(px) => px * scale,
+            val => val.resolve(size, Vec2::ZERO).unwrap_or_default(),
+        };
+
+        let offset = Vec2::new(
+            resolve(text_shadow.x_offset, node.size().x),
+            resolve(text_shadow.y_offset, node.size().y),
+        );
+
+        let translation = transform.translation + offset.extend(0.);
+        if shadow_transform.translation != translation {
+            shadow_transform.translation = translation;
+        }
+    }
+}
+
+impl ShadowCopy<'_, '_> {
+    fn copy_text(&mut self, text: Entity, shadow: Entity) {
+        let Ok([(text, font, layout), (mut shadow_text, shadow_font, mut shadow_layout)]) =
+            self.texts.get_many_mut([text, shadow])
+        else {
+            return;
+        };
+
+        if shadow_text.0 != text.0 {
+            shadow_text.0.clone_from(&text.0);
+        }
+
+        copy_font(&font, shadow_font);
+
+        if shadow_layout.justify != layout.justify || shadow_layout.linebreak != layout.linebreak {
+            *shadow_layout = *layout;
+        }
+    }
+
+    fn set_color(&mut self, entity: Entity, color: Color) {
+        _ = self.colors.get_mut(entity).map(|mut text_color| {
+            if text_color.0 != color {
+                text_color.0 = color;
+            }
+        });
+    }
+
+    fn text_spans(&self, entity: Entity) -> Vec<Entity> {
+        self.children
+            .get(entity)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|child| self.spans.contains(**child))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// mirrors the rich text spans of the text, nested spans included
+    fn copy_spans(&mut self, cmd: &mut Commands, text: Entity, shadow: Entity, color: Color) {
+        let spans = self.text_spans(text);
+        let shadow_spans = self
+            .children
+            .get(shadow)
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+
+        for (i, span) in spans.iter().enumerate() {
+            match shadow_spans.get(i) {
+                Some(shadow_span) => {
+                    let Ok([(text, font), (mut shadow_text, shadow_font)]) =
+                        self.spans.get_many_mut([*span, *shadow_span])
+                    else {
+                        continue;
+                    };
+
+                    if shadow_text.0 != text.0 {
+                        shadow_text.0.clone_from(&text.0);
+                    }
+
+                    copy_font(&font, shadow_font);
+                    self.set_color(*shadow_span, color);
+                    self.copy_spans(cmd, *span, *shadow_span, color);
+                }
+                None => {
+                    let shadow_span = self.spawn_span(cmd, *span, color);
+                    cmd.entity(shadow).add_child(shadow_span);
+                }
+            }
+        }
+
+        for shadow_span in shadow_spans.iter().skip(spans.len()) {
+            cmd.entity(*shadow_span).despawn_recursive();
+        }
+    }
+
+    fn spawn_span(&self, cmd: &mut Commands, span: Entity, color: Color) -> Entity {
+        let shadow_span = match self.spans.get(span) {
+            Ok((text, font)) => cmd.spawn((text.clone(), font.clone(), TextColor(color))),
+            Err(_) => cmd.spawn((TextSpan::default(), TextColor(color))),
+        }
+        .id();
+
+        for child in self.text_spans(span) {
+            let shadow_child = self.spawn_span(cmd, child, color);
+            cmd.entity(shadow_span).add_child(shadow_child);
+        }
+
+        shadow_span
+    }
+}
+
+fn copy_font(font: &TextFont, mut shadow_font: Mut<TextFont>) {
+    if shadow_font.font != font.font
+        || shadow_font.font_size != font.font_size
+        || shadow_font.font_smoothing != font.font_smoothing
+    {
+        *shadow_font = font.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        add_template,
+        bindings::ComponentBindings,
+        build::{HtmlNode, TemplateProperties},
+        compile::CompileContextEvent,
+        spawn_template, test_app,
+    };
+
+    fn span_texts(world: &mut World, entity: Entity, out: &mut Vec<String>) {
+        let children = world
+            .get::<Children>(entity)
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+
+        for child in children {
+            if let Some(span) = world.get::<TextSpan>(child) {
+                out.push(span.0.clone());
+                span_texts(world, child, out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shadow_mirrors_nested_spans() {
+        let mut app = test_app();
+        spawn_template(
+            &mut app,
+            r##"<template><node><text text_shadow="2px 2px #000">Hello <span>big <span>world</span></span></text><node/></node></template>"##,
+        );
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let (text, shadow) = world
+            .query::<(Entity, &UiTextShadow)>()
+            .iter(world)
+            .map(|(text, shadow)| (text, shadow.0))
+            .next()
+            .expect("text shadow");
+
+        // appended last, the text keeps its place
+        let parent = world.get::<Parent>(text).unwrap().get();
+        let siblings = world.get::<Children>(parent).unwrap().to_vec();
+        assert_eq!(siblings.last(), Some(&shadow));
+        assert_eq!(siblings.first(), Some(&text));
+
+        let mut expected = vec![];
+        span_texts(world, text, &mut expected);
+        let mut mirrored = vec![];
+        span_texts(world, shadow, &mut mirrored);
+        assert!(expected.len() >= 2);
+        assert_eq!(expected, mirrored);
+    }
+
+    #[test]
+    fn test_shadow_follows_slotted_text() {
+        let mut app = test_app();
+        let card = add_template(
+            &mut app,
+            r#"<template><property name="open">false</property><node><node if="{open}"><slot/></node></node></template>"#,
+        );
+        app.world_mut()
+            .resource_mut::<ComponentBindings>()
+            .register("card", move |mut cmd| {
+                cmd.insert(HtmlNode(card.clone()));
+            });
+
+        spawn_template(
+            &mut app,
+            r##"<template><node><card><text text_shadow="2px 2px #000">slotted</text></card></node></template>"##,
+        );
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        // the slot spawns after the shadow
+        let card = app
+            .world_mut()
+            .query_filtered::<Entity, (With<HtmlNode>, With<Parent>)>()
+            .single(app.world());
+        app.world_mut()
+            .get_mut::<TemplateProperties>(card)
+            .unwrap()
+            .insert("open".into(), "true".into());
+        app.world_mut().trigger_targets(CompileContextEvent, card);
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let (text, shadow) = world
+            .query::<(Entity, &UiTextShadow)>()
+            .iter(world)
+            .map(|(text, shadow)| (text, shadow.0))
+            .next()
+            .expect("text shadow");
+
+        let text_parent = world.get::<Parent>(text).unwrap().get();
+        let shadow_parent = world.get::<Parent>(shadow).unwrap().get();
+        assert_eq!(text_parent, shadow_parent);
+        assert!(world.get::<HtmlNode>(text_parent).is_none());
+
+        let shadows = world.query::<&TextShadowOf>().iter(world).count();
+        assert_eq!(shadows, 1);
+    }
+
+    #[test]
+    fn test_shadow_follows_moved_text() {
+        let mut app = test_app();
+        spawn_template(
+            &mut app,
+            r##"<template><node><text text_shadow="2px 2px #000">moved</text><node><node/></node></node></template>"##,
+        );
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let (text, shadow) = world
+            .query::<(Entity, &UiTextShadow)>()
+            .iter(world)
+            .map(|(text, shadow)| (text, shadow.0))
+            .next()
+            .expect("text shadow");
+        let target = world
+            .query_filtered::<Entity, (With<Node>, Without<Children>, Without<Text>)>()
+            .iter(world)
+            .next()
+            .expect("empty node");
+
+        world.entity_mut(target).add_child(text);
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<Parent>(shadow).map(|p| p.get()), Some(target));
+    }
+}
@@ -1,20 +1,61 @@
-use crate::{build::InteractionObverser, data::StyleAttr};
+use crate::{
+    build::{find_template, HtmlNode, InteractionObverser, TemplateScope},
+    data::{
+        AnimationRepeat, HtmlTemplate, KeyframeAnimation, Keyframes, MediaCompare, MediaQuery,
+        StyleAttr, StyleTransition, TextShadow,
+    },
+    focus::UiFocused,
+};
 use bevy::{
-    ecs::{query::QueryEntityError, system::SystemParam},
+    ecs::{
+        query::QueryEntityError,
+        system::{EntityCommands, SystemParam},
+    },
     prelude::*,
-    ui::widget::NodeImageMode,
+    text::FontSmoothing,
+    ui::{widget::NodeImageMode, DefaultUiCamera},
+    utils::{HashMap, HashSet},
 };
 use std::time::Duration;
 
 pub struct TransitionPlugin;
 impl Plugin for TransitionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (continues_interaction_checking, update_node_style));
+        app.add_systems(
+            Update,
+            (
+                (
+                    continues_interaction_checking,
+                    update_state_timers,
+                    update_enter_exit,
+                    update_tweens,
+                    mark_style_changes,
+                ),
+                update_node_style,
+                update_animations,
+            )
+                .chain(),
+        );
+        app.init_resource::<HuiBreakpoints>();
+        app.add_event::<PlayAnimationEvent>();
+        app.add_event::<StopAnimationEvent>();
+        app.add_observer(play_animation);
+        app.add_observer(stop_animation);
         app.register_type::<PressedTimer>();
         app.register_type::<HoverTimer>();
         app.register_type::<InteractionTimer>();
         app.register_type::<ComputedStyle>();
         app.register_type::<HtmlStyle>();
+        app.register_type::<UiDisabled>();
+        app.register_type::<UiStates>();
+        app.register_type::<StateTimers>();
+        app.register_type::<UiAnimation>();
+        app.register_type::<EnterTimer>();
+        app.register_type::<ExitTimer>();
+        app.register_type::<UiDespawning>();
+        app.register_type::<StyleTween>();
+        app.register_type::<HuiBreakpoints>();
+        app.register_type::<UiStyleOwned>();
     }
 }
 
@@ -25,6 +66,8 @@ impl Plugin for TransitionPlugin {
 pub struct InteractionTimer {
     elapsed: Duration,
     max: Duration,
+    /// progress of properties with their own `transition`
+    properties: HashMap<String, f32>,
 }
 
 /// add this component to enable
@@ -32,11 +75,137 @@ pub struct InteractionTimer {
 #[derive(Component)]
 pub struct UiActive;
 
+/// a disabled node ignores any interaction, runs no
+/// bindings and enables all `disabled:` styles.
+///
+/// In template `disabled="true"` or `disabled="{locked}"`
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect]
+pub struct UiDisabled;
+
+/// style properties owned by game code. The node style
+/// never writes them, names as in templates.
+///
+/// `
+/// cmd.entity(node).insert(UiStyleOwned::new(["width", "background"]));
+/// `
+#[derive(Component, Debug, Default, Clone, Reflect, Deref, DerefMut)]
+#[reflect]
+pub struct UiStyleOwned(HashSet<String>);
+
+impl UiStyleOwned {
+    pub fn new<'a>(properties: impl IntoIterator<Item = &'a str>) -> Self {
+        Self(properties.into_iter().map(String::from).collect())
+    }
+
+    /// keeps the owned fields of the `current` node
+    fn keep(&self, current: &Node, next: &mut Node) {
+        macro_rules! keep {
+            ($($name:literal => $field:ident),* $(,)?) => {
+                $(if self.contains($name) {
+                    next.$field = current.$field.clone();
+                })*
+            };
+        }
+
+        keep!(
+            "display" => display,
+            "position" => position_type,
+            "overflow" => overflow,
+            "overflow_clip_margin" => overflow_clip_margin,
+            "left" => left,
+            "right" => right,
+            "top" => top,
+            "bottom" => bottom,
+            "width" => width,
+            "height" => height,
+            "min_width" => min_width,
+            "min_height" => min_height,
+            "max_width" => max_width,
+            "max_height" => max_height,
+            "aspect_ratio" => aspect_ratio,
+            "align_items" => align_items,
+            "justify_items" => justify_items,
+            "align_self" => align_self,
+            "justify_self" => justify_self,
+            "align_content" => align_content,
+            "justify_content" => justify_content,
+            "margin" => margin,
+            "padding" => padding,
+            "border" => border,
+            "flex_direction" => flex_direction,
+            "flex_wrap" => flex_wrap,
+            "flex_grow" => flex_grow,
+            "flex_shrink" => flex_shrink,
+            "flex_basis" => flex_basis,
+            "row_gap" => row_gap,
+            "column_gap" => column_gap,
+            "grid_auto_flow" => grid_auto_flow,
+            "grid_template_rows" => grid_template_rows,
+            "grid_template_columns" => grid_template_columns,
+            "grid_auto_rows" => grid_auto_rows,
+            "grid_auto_columns" => grid_auto_columns,
+            "grid_row" => grid_row,
+            "grid_column" => grid_column,
+        );
+    }
+}
+
+/// the custom states of a node. Each active state
+/// enables all styles with a matching prefix.
+///
+/// In template `state(checked):background="#FFF"`
+/// `
+/// states.insert("checked");
+/// `
+#[derive(Component, Debug, Default, Clone, Reflect)]
+#[reflect]
+pub struct UiStates(HashSet<String>);
+
+impl UiStates {
+    pub fn insert(&mut self, state: impl Into<String>) -> bool {
+        self.0.insert(state.into())
+    }
+
+    pub fn remove(&mut self, state: &str) -> bool {
+        self.0.remove(state)
+    }
+
+    /// insert or remove the state
+    pub fn set(&mut self, state: impl Into<String>, enabled: bool) {
+        let state = state.into();
+        match enabled {
+            true => self.0.insert(state),
+            false => self.0.remove(&state),
+        };
+    }
+
+    pub fn toggle(&mut self, state: impl Into<String>) {
+        let state = state.into();
+        let enabled = !self.contains(&state);
+        self.set(state, enabled);
+    }
+
+    pub fn contains(&self, state: &str) -> bool {
+        self.0.contains(state)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|state| state.as_str())
+    }
+}
+
+/// transition timer of each custom state
+#[derive(Component, Default, Clone, Reflect, Deref, DerefMut)]
+#[reflect]
+pub struct StateTimers(HashMap<String, InteractionTimer>);
+
 impl InteractionTimer {
     pub fn new(max: Duration) -> Self {
         Self {
             elapsed: Duration::ZERO,
             max,
+            properties: HashMap::default(),
         }
     }
 
@@ -55,64 +224,450 @@ impl InteractionTimer {
     pub fn backward(&mut self, delta: Duration) {
         self.elapsed = self.elapsed.checked_sub(delta).unwrap_or(Duration::ZERO);
     }
+
+    /// moves into (`forward`) or out of the state, with the
+    /// `delay`, `delay_out` and `transition` timings of the style
+    pub fn step(&mut self, delta: Duration, forward: bool, style: &ComputedStyle) {
+        let fraction = self.fraction();
+        let delta = delta.as_secs_f32();
+
+        for transition in style.transitions.iter() {
+            let progress = self
+                .properties
+                .entry(transition.property.clone())
+                .or_insert(fraction);
+
+            *progress = step_fraction(
+                *progress,
+                delta,
+                forward,
+                transition.duration,
+                transition.duration_out.unwrap_or(transition.duration),
+            );
+        }
+
+        let fraction = step_fraction(
+            fraction,
+            delta,
+            forward,
+            style.delay,
+            style.delay_out.unwrap_or(style.delay),
+        );
+
+        self.max = Duration::from_secs_f32(style.delay.max(0.01));
+        self.elapsed = self.max.mul_f32(fraction);
+    }
+
+    /// progress of a property with its own `transition`
+    pub fn property_fraction(&self, property: &str) -> Option<f32> {
+        self.properties.get(property).copied()
+    }
+
+    /// true if the node and all properties left the state
+    pub fn is_zero(&self) -> bool {
+        self.elapsed.is_zero() && self.properties.values().all(|p| *p <= 0.)
+    }
+
+    /// true if the node and all properties entered the state
+    pub fn is_full(&self) -> bool {
+        self.elapsed >= self.max && self.properties.values().all(|p| *p >= 1.)
+    }
+
+    /// true if stepping in this direction changes nothing
+    pub fn is_settled(&self, forward: bool) -> bool {
+        match forward {
+            true => self.is_full(),
+            false => self.is_zero(),
+        }
+    }
+}
+
+fn step_fraction(fraction: f32, delta: f32, forward: bool, time_in: f32, time_out: f32) -> f32 {
+    match forward {
+        true => fraction + delta / time_in.max(0.01),
+        false => fraction - delta / time_out.max(0.01),
+    }
+    .clamp(0., 1.)
 }
 
 fn continues_interaction_checking(
-    interactions: Query<(Entity, &Interaction), With<HtmlStyle>>,
+    interactions: Query<(Entity, &Interaction, Has<UiDisabled>), With<HtmlStyle>>,
     mut hovers: Query<&mut HoverTimer>,
     mut presseds: Query<&mut PressedTimer>,
+    styles: Query<&HtmlStyle>,
     observer: Query<&InteractionObverser>,
     time: Res<Time<Real>>,
 ) {
-    interactions.iter().for_each(|(entity, interaction)| {
+    interactions.iter().for_each(|(entity, interaction, off)| {
+        // disabled nodes fade out of hover and pressed
+        let interaction = match off {
+            true => &Interaction::None,
+            false => interaction,
+        };
+
         let subs = observer
             .get(entity)
             .map(|obs| obs.iter())
             .unwrap_or_default()
             .chain(std::iter::once(&entity));
 
-        match interaction {
-            Interaction::Pressed => {
-                // ++ pressed ++ hover
-                subs.for_each(|sub| {
-                    if let (Ok(mut htimer), Ok(mut ptimer)) =
-                        (hovers.get_mut(*sub), presseds.get_mut(*sub))
-                    {
-                        ptimer.forward(time.delta());
-                        htimer.forward(time.delta());
-                    } else {
-                        warn!("non interacting node obsering `{sub}`")
-                    }
-                });
+        // ++ pressed ++ hover, ++ hover -- pressed, -- hover -- pressed
+        let (hovered, pressed) = match interaction {
+            Interaction::Pressed => (true, true),
+            Interaction::Hovered => (true, false),
+            Interaction::None => (false, false),
+        };
+
+        subs.for_each(|sub| {
+            if let (Ok(mut htimer), Ok(mut ptimer), Ok(style)) = (
+                hovers.get_mut(*sub),
+                presseds.get_mut(*sub),
+                styles.get(*sub),
+            ) {
+                // settled timers are not touched, no style update
+                if !htimer.is_settled(hovered) {
+                    htimer.step(time.delta(), hovered, &style.computed);
+                }
+                if !ptimer.is_settled(pressed) {
+                    ptimer.step(time.delta(), pressed, &style.computed);
+                }
+            } else {
+                warn!("non interacting node obsering `{sub}`")
             }
-            Interaction::Hovered => {
-                // ++ hover -- pressed
-                subs.for_each(|sub| {
-                    if let (Ok(mut htimer), Ok(mut ptimer)) =
-                        (hovers.get_mut(*sub), presseds.get_mut(*sub))
-                    {
-                        ptimer.backward(time.delta());
-                        htimer.forward(time.delta());
-                    } else {
-                        warn!("non interacting node obsering `{sub}`")
-                    }
-                });
+        });
+    });
+}
+
+fn update_state_timers(
+    mut nodes: Query<(&HtmlStyle, &UiStates, &mut StateTimers)>,
+    time: Res<Time<Real>>,
+) {
+    for (html_style, states, mut timers) in nodes.iter_mut() {
+        if html_style.states.is_empty() {
+            continue;
+        }
+
+        let max = Duration::from_secs_f32(html_style.computed.delay.max(0.01));
+        for state in html_style.states.keys() {
+            let active = states.contains(state);
+            let settled = match timers.get(state) {
+                Some(timer) => timer.is_settled(active),
+                None => !active,
+            };
+
+            if settled {
+                continue;
             }
-            Interaction::None => {
-                // -- hover --pressed
-                subs.for_each(|sub| {
-                    if let (Ok(mut htimer), Ok(mut ptimer)) =
-                        (hovers.get_mut(*sub), presseds.get_mut(*sub))
-                    {
-                        ptimer.backward(time.delta());
-                        htimer.backward(time.delta());
-                    } else {
-                        warn!("non interacting node obsering `{sub}`")
-                    }
-                });
+
+            let timer = timers
+                .entry(state.clone())
+                .or_insert_with(|| InteractionTimer::new(max));
+
+            timer.step(time.delta(), active, &html_style.computed);
+        }
+    }
+}
+
+/// transition from the `enter:` styles to the
+/// node style, started on spawn.
+#[derive(Component, Reflect, Clone, Default, Deref, DerefMut)]
+#[reflect]
+pub struct EnterTimer(InteractionTimer);
+
+impl EnterTimer {
+    pub fn new(d: Duration) -> Self {
+        Self(InteractionTimer::new(d))
+    }
+}
+
+/// transition to the `exit:` styles, started by
+/// [DespawnAnimatedExt::despawn_animated].
+#[derive(Component, Reflect, Clone, Default, Deref, DerefMut)]
+#[reflect]
+pub struct ExitTimer(InteractionTimer);
+
+impl ExitTimer {
+    pub fn new(d: Duration) -> Self {
+        Self(InteractionTimer::new(d))
+    }
+}
+
+/// despawns the node, once all exit transitions are done
+#[derive(Component, Reflect, Clone, Default, Deref, DerefMut)]
+#[reflect]
+pub struct UiDespawning(Timer);
+
+pub trait DespawnAnimatedExt {
+    /// plays the `exit:` styles of the node and its
+    /// children, then despawns it recursively.
+    ///
+    /// despawns right away, if there are none.
+    fn despawn_animated(&mut self);
+}
+
+impl DespawnAnimatedExt for EntityCommands<'_> {
+    fn despawn_animated(&mut self) {
+        self.queue(despawn_animated);
+    }
+}
+
+fn despawn_animated(entity: Entity, world: &mut World) {
+    // queued more than once, or despawned in between
+    let Ok(root) = world.get_entity(entity) else {
+        return;
+    };
+
+    if root.contains::<UiDespawning>() {
+        return;
+    }
+
+    let mut exits = vec![];
+    let mut stack = vec![entity];
+    while let Some(current) = stack.pop() {
+        if let Some(style) = world.get::<HtmlStyle>(current) {
+            if !style.exit.is_empty() {
+                exits.push((current, style.computed.longest_transition()));
             }
+        }
+
+        if let Some(children) = world.get::<Children>(current) {
+            stack.extend(children.iter());
+        }
+    }
+
+    let Ok(root) = world.get_entity_mut(entity) else {
+        return;
+    };
+
+    if exits.is_empty() {
+        root.despawn_recursive();
+        return;
+    }
+
+    let mut longest = 0f32;
+    for (node, delay) in exits {
+        world
+            .entity_mut(node)
+            .insert(ExitTimer::new(Duration::from_secs_f32(delay)));
+        longest = longest.max(delay);
+    }
+
+    world
+        .entity_mut(entity)
+        .insert(UiDespawning(Timer::from_seconds(longest, TimerMode::Once)));
+}
+
+fn update_enter_exit(
+    mut cmd: Commands,
+    mut enters: Query<(Entity, &mut EnterTimer, &HtmlStyle)>,
+    mut exits: Query<(&mut ExitTimer, &HtmlStyle)>,
+    mut despawns: Query<(Entity, &mut UiDespawning)>,
+    time: Res<Time<Real>>,
+) {
+    for (entity, mut timer, html_style) in enters.iter_mut() {
+        timer.step(time.delta(), true, &html_style.computed);
+        if timer.is_full() {
+            cmd.entity(entity).remove::<EnterTimer>();
+        }
+    }
+
+    for (mut timer, html_style) in exits.iter_mut() {
+        if !timer.is_settled(true) {
+            timer.step(time.delta(), true, &html_style.computed);
+        }
+    }
+
+    for (entity, mut despawn) in despawns.iter_mut() {
+        if despawn.tick(time.delta()).finished() {
+            cmd.entity(entity).try_despawn_recursive();
+        }
+    }
+}
+
+/// tweens the style changes of a recompile, from
+/// the previous values to the new ones.
+#[derive(Component, Clone, Default, Reflect)]
+#[reflect]
+pub struct StyleTween {
+    from: Option<ComputedStyle>,
+    styles: Vec<StyleAttr>,
+    timer: InteractionTimer,
+}
+
+impl StyleTween {
+    /// tweens `styles` starting at the `from` values. Properties
+    /// of an interrupted tween keep easing to their target.
+    pub fn start(&mut self, from: ComputedStyle, mut styles: Vec<StyleAttr>) {
+        if styles.is_empty() {
+            return;
+        }
+
+        if self.is_running() {
+            for style in self.styles.drain(..) {
+                if !styles.iter().any(|s| s.name() == style.name()) {
+                    styles.push(style);
+                }
+            }
+        }
+
+        self.timer = InteractionTimer::new(Duration::from_secs_f32(from.delay.max(0.01)));
+        self.from = Some(from);
+        self.styles = styles;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.from.is_some()
+    }
+
+    /// the tweened values right now, `None` if not running
+    pub fn current(&self, target: &ComputedStyle) -> Option<ComputedStyle> {
+        let from = self.from.as_ref()?;
+        let ratio = target.ease(self.timer.fraction());
+        let mut current = HtmlStyle {
+            computed: from.clone(),
+            ..default()
         };
-    });
+
+        for style in self.styles.iter() {
+            let ratio = transition_ratio(from, style, ratio, Some(&self.timer));
+            if let Some(value) = lerp_attr(from, style, ratio) {
+                current.add_style_attr(value);
+            }
+        }
+
+        Some(current.computed)
+    }
+}
+
+fn update_tweens(mut tweens: Query<(&mut StyleTween, &HtmlStyle)>, time: Res<Time<Real>>) {
+    for (mut tween, html_style) in tweens.iter_mut() {
+        if !tween.is_running() {
+            continue;
+        }
+
+        tween.timer.step(time.delta(), true, &html_style.computed);
+        if tween.timer.is_full() {
+            tween.from = None;
+            tween.styles.clear();
+        }
+    }
+}
+
+/// named breakpoints for media prefixes `@sm:width="50%"`.
+///
+/// Defaults to minimal widths `sm` 640, `md` 768, `lg` 1024 and `xl` 1280.
+///
+/// `
+/// app.insert_resource(HuiBreakpoints::default().with("handheld", MediaQuery::Width(MediaCompare::Less, 800.)));
+/// `
+#[derive(Resource, Debug, Clone, Reflect, Deref, DerefMut)]
+#[reflect(Resource)]
+pub struct HuiBreakpoints(HashMap<String, MediaQuery>);
+
+impl Default for HuiBreakpoints {
+    fn default() -> Self {
+        Self(
+            [("sm", 640.), ("md", 768.), ("lg", 1024.), ("xl", 1280.)]
+                .into_iter()
+                .map(|(name, width)| {
+                    let query = MediaQuery::Width(MediaCompare::GreaterEqual, width);
+                    (name.to_string(), query)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl HuiBreakpoints {
+    pub fn with(mut self, name: &str, query: MediaQuery) -> Self {
+        self.insert(name.to_string(), query);
+        self
+    }
+}
+
+/// the logical viewport size of a node, the `TargetCamera`
+/// viewport or the default ui camera of the primary window.
+#[derive(SystemParam)]
+pub struct UiViewport<'w, 's> {
+    breakpoints: Res<'w, HuiBreakpoints>,
+    default_camera: DefaultUiCamera<'w, 's>,
+    cameras: Query<'w, 's, &'static Camera>,
+    targets: Query<'w, 's, &'static TargetCamera>,
+    parents: Query<'w, 's, &'static Parent>,
+}
+
+impl UiViewport<'_, '_> {
+    pub fn size(&self, entity: Entity) -> Option<Vec2> {
+        self.camera(entity)
+            .and_then(|camera| self.cameras.get(camera).ok())
+            .and_then(|camera| camera.logical_viewport_size())
+    }
+
+    /// the camera of the closest `TargetCamera` up
+    /// the hierarchy, usually set on the ui root.
+    pub fn camera(&self, entity: Entity) -> Option<Entity> {
+        std::iter::once(entity)
+            .chain(self.parents.iter_ancestors(entity))
+            .find_map(|ancestor| self.targets.get(ancestor).ok())
+            .map(|target| target.0)
+            .or_else(|| self.default_camera.get())
+    }
+
+    pub fn matches(&self, size: Vec2, query: &MediaQuery) -> bool {
+        match query {
+            MediaQuery::Width(compare, width) => compare.eval(size.x, *width),
+            MediaQuery::Height(compare, height) => compare.eval(size.y, *height),
+            MediaQuery::Breakpoint(name) => match self.breakpoints.get(name) {
+                // breakpoints of breakpoints are not resolved
+                Some(MediaQuery::Breakpoint(_)) | None => false,
+                Some(query) => self.matches(size, query),
+            },
+        }
+    }
+}
+
+type StyleFlagAdded = Or<(Added<UiActive>, Added<UiFocused>, Added<UiDisabled>)>;
+
+#[derive(SystemParam)]
+struct StyleFlagRemoved<'w, 's> {
+    active: RemovedComponents<'w, 's, UiActive>,
+    focused: RemovedComponents<'w, 's, UiFocused>,
+    disabled: RemovedComponents<'w, 's, UiDisabled>,
+    animation: RemovedComponents<'w, 's, UiAnimation>,
+    owned: RemovedComponents<'w, 's, UiStyleOwned>,
+}
+
+/// flags node styles for an update, if something
+/// outside of the style and timers changed.
+fn mark_style_changes(
+    mut styles: Query<&mut HtmlStyle>,
+    added: Query<Entity, StyleFlagAdded>,
+    mut removed: StyleFlagRemoved,
+    targets: Query<Entity, Changed<TargetCamera>>,
+    cameras: Query<(), Changed<Camera>>,
+    breakpoints: Res<HuiBreakpoints>,
+) {
+    let entities = added
+        .iter()
+        .chain(removed.active.read())
+        .chain(removed.focused.read())
+        .chain(removed.disabled.read())
+        .chain(removed.animation.read())
+        .chain(removed.owned.read())
+        .chain(targets.iter())
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        _ = styles.get_mut(entity).map(|mut style| style.set_changed());
+    }
+
+    // resized viewports re-evaluate media styles
+    if breakpoints.is_changed() || !cameras.is_empty() {
+        styles
+            .iter_mut()
+            .filter(|style| !style.media.is_empty())
+            .for_each(|mut style| style.set_changed());
+    }
 }
 
 #[derive(SystemParam)]
@@ -121,47 +676,170 @@ pub struct UiStyleQuery<'w, 's> {
     pub node: Query<'w, 's, &'static mut Node>,
     pub text_fonts: Query<'w, 's, &'static mut TextFont>,
     pub text_colors: Query<'w, 's, &'static mut TextColor>,
+    pub text_layouts: Query<'w, 's, &'static mut TextLayout>,
     pub background: Query<'w, 's, &'static mut BackgroundColor>,
     pub border_radius: Query<'w, 's, &'static mut BorderRadius>,
     pub border_color: Query<'w, 's, &'static mut BorderColor>,
     pub shadow: Query<'w, 's, &'static mut BoxShadow>,
     pub outline: Query<'w, 's, &'static mut Outline>,
+    pub z_index: Query<'w, 's, &'static mut ZIndex>,
+    pub global_z_index: Query<'w, 's, &'static mut GlobalZIndex>,
+    pub visibility: Query<'w, 's, &'static mut Visibility>,
+    pub images: Query<'w, 's, &'static mut ImageNode>,
+    pub owned: Query<'w, 's, &'static UiStyleOwned>,
+    pub cmd: Commands<'w, 's>,
 }
 
 impl<'w, 's> UiStyleQuery<'w, 's> {
+    /// applies conditional styles at the node `ratio`. Properties
+    /// with their own `transition` follow the timer instead.
+    pub fn apply_transition(
+        &mut self,
+        entity: Entity,
+        computed: &ComputedStyle,
+        styles: &[StyleAttr],
+        ratio: f32,
+        timer: Option<&InteractionTimer>,
+    ) {
+        for style in styles.iter() {
+            let ratio = transition_ratio(computed, style, ratio, timer);
+
+            // spans only have text styles
+            _ = self.apply_interpolated(entity, ratio, computed, style);
+        }
+    }
+
+    /// the `enter:` styles fade out, while the timer
+    /// runs forward with the enter timings.
+    pub fn apply_enter(
+        &mut self,
+        entity: Entity,
+        computed: &ComputedStyle,
+        styles: &[StyleAttr],
+        timer: &InteractionTimer,
+    ) {
+        let ratio = computed.ease(timer.fraction());
+        for style in styles.iter() {
+            let ratio = 1. - transition_ratio(computed, style, ratio, Some(timer));
+            _ = self.apply_interpolated(entity, ratio, computed, style);
+        }
+    }
+
     pub fn apply_computed(&mut self, entity: Entity, computed: &ComputedStyle) {
-        _ = self.node.get_mut(entity).map(|mut node| {
-            node.clone_from(&computed.node);
+        let owned = self.owned.get(entity).ok();
+        let is_owned = |name: &str| owned.is_some_and(|owned| owned.contains(name));
+
+        _ = self.node.get_mut(entity).map(|mut node| match owned {
+            Some(owned) => {
+                let mut next = computed.node.clone();
+                owned.keep(&node, &mut next);
+                *node = next;
+            }
+            None => node.clone_from(&computed.node),
         });
 
         _ = self.text_fonts.get_mut(entity).map(|mut font| {
-            font.font_size = computed.font_size;
-            font.font = computed.font.clone();
+            if !is_owned("font_size") {
+                font.font_size = computed.font_size;
+            }
+            if !is_owned("font") {
+                font.font = computed.font.clone();
+            }
+            if !is_owned("font_smoothing") {
+                font.font_smoothing = computed.font_smoothing;
+            }
         });
 
-        _ = self.text_colors.get_mut(entity).map(|mut color| {
-            **color = computed.font_color;
+        _ = self.text_layouts.get_mut(entity).map(|mut layout| {
+            if !is_owned("text_justify") {
+                layout.justify = computed.text_justify;
+            }
+            if !is_owned("line_break") {
+                layout.linebreak = computed.line_break;
+            }
         });
 
-        _ = self.background.get_mut(entity).map(|mut background| {
-            background.0 = computed.background;
-        });
+        if !is_owned("font_color") {
+            _ = self.text_colors.get_mut(entity).map(|mut color| {
+                **color = computed.font_color;
+            });
+        }
 
-        _ = self.border_radius.get_mut(entity).map(|mut radius| {
-            radius.top_left = computed.border_radius.top;
-            radius.top_right = computed.border_radius.right;
-            radius.bottom_right = computed.border_radius.bottom;
-            radius.bottom_left = computed.border_radius.left;
-        });
+        if !is_owned("background") {
+            _ = self.background.get_mut(entity).map(|mut background| {
+                background.0 = computed.background;
+            });
+        }
+
+        if !is_owned("border_radius") {
+            _ = self.border_radius.get_mut(entity).map(|mut radius| {
+                radius.top_left = computed.border_radius.top;
+                radius.top_right = computed.border_radius.right;
+                radius.bottom_right = computed.border_radius.bottom;
+                radius.bottom_left = computed.border_radius.left;
+            });
+        }
 
         if let Some(computed_shadow) = computed.shadow {
             _ = self.shadow.get_mut(entity).map(|mut shadow| {
+                let previous = *shadow;
                 *shadow = computed_shadow;
+                if is_owned("shadow_color") {
+                    shadow.color = previous.color;
+                }
+                if is_owned("shadow_offset") {
+                    shadow.x_offset = previous.x_offset;
+                    shadow.y_offset = previous.y_offset;
+                }
+                if is_owned("shadow_spread") {
+                    shadow.spread_radius = previous.spread_radius;
+                }
+                if is_owned("shadow_blur") {
+                    shadow.blur_radius = previous.blur_radius;
+                }
+            });
+        }
+
+        if !is_owned("border_color") {
+            _ = self.border_color.get_mut(entity).map(|mut color| {
+                color.0 = computed.border_color;
+            });
+        }
+
+        if let Some(z_index) = computed.z_index.filter(|_| !is_owned("z_index")) {
+            _ = self.z_index.get_mut(entity).map(|mut current| {
+                current.set_if_neq(ZIndex(z_index));
+            });
+        }
+
+        if let Some(global_z_index) = computed
+            .global_z_index
+            .filter(|_| !is_owned("global_z_index"))
+        {
+            set_global_z_index(
+                &mut self.global_z_index,
+                &mut self.cmd,
+                entity,
+                Some(global_z_index),
+            );
+        }
+
+        if let Some(visibility) = computed.visibility.filter(|_| !is_owned("visibility")) {
+            _ = self.visibility.get_mut(entity).map(|mut current| {
+                current.set_if_neq(visibility);
             });
         }
 
-        _ = self.border_color.get_mut(entity).map(|mut color| {
-            color.0 = computed.border_color;
+        _ = self.images.get_mut(entity).map(|mut image| {
+            if !is_owned("image_color") {
+                image.color = computed.image_color;
+            }
+            if !is_owned("flip_x") {
+                image.flip_x = computed.flip_x;
+            }
+            if !is_owned("flip_y") {
+                image.flip_y = computed.flip_y;
+            }
         });
     }
 
@@ -172,11 +850,66 @@ impl<'w, 's> UiStyleQuery<'w, 's> {
         computed: &ComputedStyle,
         attr: &StyleAttr,
     ) -> Result<(), QueryEntityError> {
+        if self
+            .owned
+            .get(entity)
+            .is_ok_and(|owned| owned.contains(attr.name()))
+        {
+            return Ok(());
+        }
+
+        // text spans have no node
+        match attr {
+            StyleAttr::FontColor(color) => {
+                _ = self.text_colors.get_mut(entity).map(|mut tc| {
+                    **tc = lerp_color(&computed.font_color, color, ratio);
+                });
+                return Ok(());
+            }
+            StyleAttr::FontSize(s) => {
+                _ = self.text_fonts.get_mut(entity).map(|mut txt| {
+                    txt.font_size = computed.font_size.lerp(*s, ratio);
+                });
+                return Ok(());
+            }
+            StyleAttr::Font(h) => {
+                _ = self.text_fonts.get_mut(entity).map(|mut txt| {
+                    txt.font = self.server.load(h);
+                });
+                return Ok(());
+            }
+            StyleAttr::FontSmoothing(smoothing) => {
+                _ = self.text_fonts.get_mut(entity).map(|mut txt| {
+                    txt.font_smoothing = snap(computed.font_smoothing, *smoothing, ratio);
+                });
+                return Ok(());
+            }
+            StyleAttr::TextJustify(justify) => {
+                _ = self.text_layouts.get_mut(entity).map(|mut layout| {
+                    layout.justify = snap(computed.text_justify, *justify, ratio);
+                });
+                return Ok(());
+            }
+            StyleAttr::LineBreak(linebreak) => {
+                _ = self.text_layouts.get_mut(entity).map(|mut layout| {
+                    layout.linebreak = snap(computed.line_break, *linebreak, ratio);
+                });
+                return Ok(());
+            }
+            _ => (),
+        }
+
         let mut style = self.node.get_mut(entity)?;
+        let node = &computed.node;
         match attr {
-            StyleAttr::Display(display) => style.display = *display,
-            StyleAttr::Position(position_type) => style.position_type = *position_type,
-            StyleAttr::Overflow(overflow) => style.overflow = *overflow,
+            StyleAttr::Display(display) => style.display = snap(node.display, *display, ratio),
+            StyleAttr::Position(position_type) => {
+                style.position_type = snap(node.position_type, *position_type, ratio)
+            }
+            StyleAttr::Overflow(overflow) => style.overflow = snap(node.overflow, *overflow, ratio),
+            StyleAttr::OverflowClipMargin(clip) => {
+                style.overflow_clip_margin = snap(node.overflow_clip_margin, *clip, ratio)
+            }
             StyleAttr::Left(val) => style.left = lerp_val(&computed.node.left, val, ratio),
             StyleAttr::Right(val) => style.right = lerp_val(&computed.node.right, val, ratio),
             StyleAttr::Top(val) => style.top = lerp_val(&computed.node.top, val, ratio),
@@ -196,14 +929,30 @@ impl<'w, 's> UiStyleQuery<'w, 's> {
                 style.max_height = lerp_val(&computed.node.max_height, val, ratio)
             }
             StyleAttr::AspectRatio(f) => {
-                style.aspect_ratio = computed.node.aspect_ratio.map(|a| a.lerp(*f, ratio))
-            }
-            StyleAttr::AlignItems(align_items) => style.align_items = *align_items,
-            StyleAttr::JustifyItems(justify_items) => style.justify_items = *justify_items,
-            StyleAttr::AlignSelf(align_self) => style.align_self = *align_self,
-            StyleAttr::JustifySelf(justify_self) => style.justify_self = *justify_self,
-            StyleAttr::AlignContent(align_content) => style.align_content = *align_content,
-            StyleAttr::JustifyContent(justify_content) => style.justify_content = *justify_content,
+                style.aspect_ratio = computed
+                    .node
+                    .aspect_ratio
+                    .map(|a| a.lerp(*f, ratio))
+                    .or(Some(*f).filter(|_| ratio > 0.))
+            }
+            StyleAttr::AlignItems(align_items) => {
+                style.align_items = snap(node.align_items, *align_items, ratio)
+            }
+            StyleAttr::JustifyItems(justify_items) => {
+                style.justify_items = snap(node.justify_items, *justify_items, ratio)
+            }
+            StyleAttr::AlignSelf(align_self) => {
+                style.align_self = snap(node.align_self, *align_self, ratio)
+            }
+            StyleAttr::JustifySelf(justify_self) => {
+                style.justify_self = snap(node.justify_self, *justify_self, ratio)
+            }
+            StyleAttr::AlignContent(align_content) => {
+                style.align_content = snap(node.align_content, *align_content, ratio)
+            }
+            StyleAttr::JustifyContent(justify_content) => {
+                style.justify_content = snap(node.justify_content, *justify_content, ratio)
+            }
             StyleAttr::Margin(ui_rect) => {
                 style.margin = lerp_rect(&computed.node.margin, ui_rect, ratio)
             }
@@ -239,8 +988,12 @@ impl<'w, 's> UiStyleQuery<'w, 's> {
                         lerp_val(&computed.border_radius.left, &ui_rect.left, ratio);
                 });
             }
-            StyleAttr::FlexDirection(flex_direction) => style.flex_direction = *flex_direction,
-            StyleAttr::FlexWrap(flex_wrap) => style.flex_wrap = *flex_wrap,
+            StyleAttr::FlexDirection(flex_direction) => {
+                style.flex_direction = snap(node.flex_direction, *flex_direction, ratio)
+            }
+            StyleAttr::FlexWrap(flex_wrap) => {
+                style.flex_wrap = snap(node.flex_wrap, *flex_wrap, ratio)
+            }
             StyleAttr::FlexGrow(g) => style.flex_grow = computed.node.flex_grow.lerp(*g, ratio),
             StyleAttr::FlexShrink(s) => {
                 style.flex_shrink = computed.node.flex_shrink.lerp(*s, ratio)
@@ -252,34 +1005,33 @@ impl<'w, 's> UiStyleQuery<'w, 's> {
             StyleAttr::ColumnGap(val) => {
                 style.column_gap = lerp_val(&computed.node.max_height, val, ratio)
             }
-            StyleAttr::GridAutoFlow(grid_auto_flow) => style.grid_auto_flow = *grid_auto_flow,
-            StyleAttr::GridTemplateRows(vec) => style.grid_template_rows = vec.clone(),
-            StyleAttr::GridTemplateColumns(vec) => style.grid_template_columns = vec.clone(),
-            StyleAttr::GridAutoRows(vec) => style.grid_auto_rows = vec.clone(),
-            StyleAttr::GridAutoColumns(vec) => style.grid_auto_columns = vec.clone(),
-            StyleAttr::GridRow(grid_placement) => style.grid_row = *grid_placement,
-            StyleAttr::GridColumn(grid_placement) => style.grid_column = *grid_placement,
+            StyleAttr::GridAutoFlow(grid_auto_flow) => {
+                style.grid_auto_flow = snap(node.grid_auto_flow, *grid_auto_flow, ratio)
+            }
+            StyleAttr::GridTemplateRows(vec) => {
+                style.grid_template_rows = snap(&node.grid_template_rows, vec, ratio).clone()
+            }
+            StyleAttr::GridTemplateColumns(vec) => {
+                style.grid_template_columns = snap(&node.grid_template_columns, vec, ratio).clone()
+            }
+            StyleAttr::GridAutoRows(vec) => {
+                style.grid_auto_rows = snap(&node.grid_auto_rows, vec, ratio).clone()
+            }
+            StyleAttr::GridAutoColumns(vec) => {
+                style.grid_auto_columns = snap(&node.grid_auto_columns, vec, ratio).clone()
+            }
+            StyleAttr::GridRow(grid_placement) => {
+                style.grid_row = snap(node.grid_row, *grid_placement, ratio)
+            }
+            StyleAttr::GridColumn(grid_placement) => {
+                style.grid_column = snap(node.grid_column, *grid_placement, ratio)
+            }
             StyleAttr::Background(color) => {
                 _ = self
                     .background
                     .get_mut(entity)
                     .map(|mut bg| bg.0 = lerp_color(&computed.background, color, ratio));
             }
-            StyleAttr::FontColor(color) => {
-                _ = self.text_colors.get_mut(entity).map(|mut tc| {
-                    **tc = lerp_color(&computed.font_color, color, ratio);
-                });
-            }
-            StyleAttr::FontSize(s) => {
-                _ = self.text_fonts.get_mut(entity).map(|mut txt| {
-                    txt.font_size = computed.font_size.lerp(*s, ratio);
-                });
-            }
-            StyleAttr::Font(h) => {
-                _ = self.text_fonts.get_mut(entity).map(|mut txt| {
-                    txt.font = self.server.load(h);
-                });
-            }
             StyleAttr::ShadowColor(color) => {
                 if let Some(computed_shadow) = computed.shadow {
                     _ = self.shadow.get_mut(entity).map(|mut shadow| {
@@ -310,6 +1062,43 @@ impl<'w, 's> UiStyleQuery<'w, 's> {
                     });
                 }
             }
+            // layering snaps as soon as a state starts
+            StyleAttr::ZIndex(z_index) => {
+                let z_index = snap(computed.z_index.unwrap_or_default(), *z_index, ratio);
+                _ = self
+                    .z_index
+                    .get_mut(entity)
+                    .map(|mut z| z.set_if_neq(ZIndex(z_index)));
+            }
+            StyleAttr::GlobalZIndex(z_index) => {
+                let z_index = snap(computed.global_z_index, Some(*z_index), ratio);
+                set_global_z_index(&mut self.global_z_index, &mut self.cmd, entity, z_index);
+            }
+            StyleAttr::Visibility(visibility) => {
+                let visibility = snap(computed.visibility.unwrap_or_default(), *visibility, ratio);
+                _ = self
+                    .visibility
+                    .get_mut(entity)
+                    .map(|mut v| v.set_if_neq(visibility));
+            }
+            StyleAttr::ImageColor(color) => {
+                _ = self
+                    .images
+                    .get_mut(entity)
+                    .map(|mut image| image.color = lerp_color(&computed.image_color, color, ratio));
+            }
+            StyleAttr::FlipX(flip) => {
+                _ = self
+                    .images
+                    .get_mut(entity)
+                    .map(|mut image| image.flip_x = snap(computed.flip_x, *flip, ratio));
+            }
+            StyleAttr::FlipY(flip) => {
+                _ = self
+                    .images
+                    .get_mut(entity)
+                    .map(|mut image| image.flip_y = snap(computed.flip_y, *flip, ratio));
+            }
             _ => (),
         }
 
@@ -317,56 +1106,214 @@ impl<'w, 's> UiStyleQuery<'w, 's> {
     }
 }
 
+/// nodes with a changed style or a transition in flight
+type StyleChanged = Or<(
+    Changed<HtmlStyle>,
+    Changed<HoverTimer>,
+    Changed<PressedTimer>,
+    Changed<StateTimers>,
+    Changed<EnterTimer>,
+    Changed<ExitTimer>,
+    Changed<StyleTween>,
+)>;
+
+/// writes the node style into the bevy components. Only
+/// touches nodes with a changed style or running transition,
+/// see [mark_style_changes].
 fn update_node_style(
-    nodes: Query<(Entity, &HtmlStyle, Has<UiActive>)>,
+    nodes: Query<(Entity, &HtmlStyle), StyleChanged>,
+    flags: Query<(Has<UiActive>, Has<UiFocused>, Has<UiDisabled>)>,
+    viewport: UiViewport,
     mut ui_style: UiStyleQuery,
-    hover_timer: Query<&HoverTimer>,
-    press_timer: Query<&PressedTimer>,
+    interaction_timers: Query<(Option<&HoverTimer>, Option<&PressedTimer>)>,
+    state_timers: Query<&StateTimers>,
+    transitions: Query<(Option<&EnterTimer>, Option<&ExitTimer>, Option<&StyleTween>)>,
 ) {
-    for (entity, html_style, is_active) in nodes.iter() {
-        ui_style.apply_computed(entity, &html_style.computed);
+    for (entity, html_style) in nodes.iter() {
+        let (is_active, is_focused, is_disabled) = flags.get(entity).unwrap_or_default();
 
-        let hover_ratio = hover_timer
-            .get(entity)
-            .map(|t| t.fraction())
-            .unwrap_or_default();
+        // matching media styles are part of the base style,
+        // re-evaluated on resize
+        let with_media;
+        let computed = match html_style.media.is_empty() {
+            true => &html_style.computed,
+            false => {
+                let size = viewport.size(entity).unwrap_or_default();
+                let mut base = HtmlStyle {
+                    computed: html_style.computed.clone(),
+                    ..default()
+                };
+                html_style
+                    .media
+                    .iter()
+                    .filter(|(query, _)| viewport.matches(size, query))
+                    .flat_map(|(_, styles)| styles.iter())
+                    .for_each(|style| base.add_style_attr(style.clone()));
+                with_media = base.computed;
+                &with_media
+            }
+        };
 
-        let hover_ratio = html_style
-            .computed
-            .easing
-            .map(|ease| EasingCurve::new(0., 1., ease).sample(hover_ratio))
-            .flatten()
-            .unwrap_or(hover_ratio);
+        ui_style.apply_computed(entity, computed);
 
-        for hover_style in html_style.hover.iter() {
-            ui_style
-                .apply_interpolated(entity, hover_ratio, &html_style.computed, hover_style)
-                .expect("node has no style, impossible");
+        let ratio = |timer: Option<&InteractionTimer>| {
+            timer
+                .map(|t| computed.ease(t.fraction()))
+                .unwrap_or_default()
+        };
+
+        let (enter, exit, tween) = transitions.get(entity).unwrap_or_default();
+
+        // recompiled values start at their previous value
+        if let Some((from, tween)) = tween.and_then(|t| t.from.as_ref().zip(Some(t))) {
+            let timer = Some(&tween.timer);
+            ui_style.apply_transition(entity, from, &tween.styles, ratio(timer), timer);
         }
 
-        let press_ratio = press_timer
-            .get(entity)
-            .map(|t| t.fraction())
-            .unwrap_or_default();
+        let (hover, press) = interaction_timers.get(entity).unwrap_or_default();
+
+        let hover = hover.map(|t| &**t);
+        ui_style.apply_transition(entity, computed, &html_style.hover, ratio(hover), hover);
+
+        let press = press.map(|t| &**t);
+        ui_style.apply_transition(entity, computed, &html_style.pressed, ratio(press), press);
+
+        let active_ratio = if is_active { 1. } else { 0. };
+        ui_style.apply_transition(entity, computed, &html_style.active, active_ratio, None);
+
+        let timers = state_timers.get(entity).ok();
+        for (state, state_styles) in html_style.states.iter() {
+            let timer = timers.and_then(|timers| timers.get(state));
+            ui_style.apply_transition(entity, computed, state_styles, ratio(timer), timer);
+        }
+
+        let focus_ratio = if is_focused { 1. } else { 0. };
+        ui_style.apply_transition(entity, computed, &html_style.focus, focus_ratio, None);
+
+        let disabled_ratio = if is_disabled { 1. } else { 0. };
+        ui_style.apply_transition(entity, computed, &html_style.disabled, disabled_ratio, None);
+
+        if let Some(enter) = enter {
+            ui_style.apply_enter(entity, computed, &html_style.enter, enter);
+        }
+
+        let exit = exit.map(|t| &**t);
+        ui_style.apply_transition(entity, computed, &html_style.exit, ratio(exit), exit);
+    }
+}
+
+/// a playing keyframe animation, applied on
+/// top of the node style.
+///
+/// removed when the animation ends, the node
+/// returns to its style.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect]
+pub struct UiAnimation {
+    pub animation: KeyframeAnimation,
+    pub keyframes: Keyframes,
+    /// seconds since the start
+    pub elapsed: f32,
+}
+
+impl UiAnimation {
+    pub fn new(animation: KeyframeAnimation, keyframes: Keyframes) -> Self {
+        Self {
+            animation,
+            keyframes,
+            elapsed: 0.,
+        }
+    }
 
-        let press_ratio = html_style
-            .computed
-            .easing
-            .map(|ease| EasingCurve::new(0., 1., ease).sample(press_ratio))
-            .flatten()
-            .unwrap_or(press_ratio);
+    /// position in the current iteration `0..=1`,
+    /// `None` if finished.
+    pub fn progress(&self) -> Option<f32> {
+        let cycles = self.elapsed / self.animation.duration.max(0.001);
+        if let AnimationRepeat::Count(count) = self.animation.repeat {
+            if cycles >= count as f32 {
+                return None;
+            }
+        }
 
-        for press_style in html_style.pressed.iter() {
-            ui_style
-                .apply_interpolated(entity, press_ratio, &html_style.computed, press_style)
-                .expect("node has no style, impossible");
+        let t = cycles.fract();
+        match self.animation.alternate && cycles as u32 % 2 == 1 {
+            true => Some(1. - t),
+            false => Some(t),
         }
+    }
+}
+
+/// plays a keyframe animation of the node's template,
+/// replaces the current one.
+///
+/// `
+/// cmd.trigger_targets(PlayAnimationEvent(KeyframeAnimation::new("pulse", 1.).infinite()), entity);
+/// `
+#[derive(Event, Debug, Clone)]
+pub struct PlayAnimationEvent(pub KeyframeAnimation);
+
+/// stops the current keyframe animation
+#[derive(Event)]
+pub struct StopAnimationEvent;
 
-        let active_ratio = is_active.then_some(1.).unwrap_or_default();
-        for active_style in html_style.active.iter() {
-            ui_style
-                .apply_interpolated(entity, active_ratio, &html_style.computed, active_style)
-                .expect("node has no style, impossible");
+fn play_animation(
+    trigger: Trigger<PlayAnimationEvent>,
+    mut cmd: Commands,
+    scopes: Query<&TemplateScope>,
+    html_nodes: Query<&HtmlNode>,
+    templates: Res<Assets<HtmlTemplate>>,
+) {
+    let entity = trigger.entity();
+    let animation = &trigger.event().0;
+
+    // keyframes are local to the template
+    let keyframes = find_template(entity, &scopes, &html_nodes)
+        .and_then(|handle| templates.get(&**handle))
+        .and_then(|template| template.keyframes.get(&animation.name));
+
+    match keyframes {
+        Some(keyframes) => {
+            cmd.entity(entity)
+                .try_insert(UiAnimation::new(animation.clone(), keyframes.clone()));
+        }
+        None => warn!("keyframes `{}` do not exist", animation.name),
+    }
+}
+
+fn stop_animation(trigger: Trigger<StopAnimationEvent>, mut cmd: Commands) {
+    cmd.entity(trigger.entity()).remove::<UiAnimation>();
+}
+
+fn update_animations(
+    mut cmd: Commands,
+    mut animations: Query<(Entity, &mut UiAnimation, &HtmlStyle)>,
+    mut ui_style: UiStyleQuery,
+    time: Res<Time<Real>>,
+) {
+    for (entity, mut animation, html_style) in animations.iter_mut() {
+        animation.elapsed += time.delta_secs();
+        let Some(t) = animation.progress() else {
+            cmd.entity(entity).remove::<UiAnimation>();
+            continue;
+        };
+
+        for (from, to, ratio) in animation.keyframes.sample(t) {
+            let ratio = animation
+                .animation
+                .easing
+                .and_then(|ease| EasingCurve::new(0., 1., ease).sample(ratio))
+                .unwrap_or(ratio);
+
+            let mut start = HtmlStyle {
+                computed: html_style.computed.clone(),
+                ..default()
+            };
+
+            if let Some(from) = from {
+                start.add_style_attr(from.clone());
+            }
+
+            _ = ui_style.apply_interpolated(entity, ratio, &start.computed, to);
         }
     }
 }
@@ -399,14 +1346,49 @@ pub struct ComputedStyle {
     pub border_radius: UiRect,
     pub image_mode: Option<NodeImageMode>,
     pub image_region: Option<Rect>,
+    pub image_color: Color,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub z_index: Option<i32>,
+    pub global_z_index: Option<i32>,
+    pub visibility: Option<Visibility>,
     pub shadow: Option<BoxShadow>,
     pub background: Color,
     pub outline: Option<Outline>,
     pub font: Handle<Font>,
     pub font_size: f32,
     pub font_color: Color,
+    pub font_smoothing: FontSmoothing,
+    pub text_justify: JustifyText,
+    pub line_break: LineBreak,
+    pub text_shadow: Option<TextShadow>,
     pub delay: f32,
+    pub delay_out: Option<f32>,
     pub easing: Option<EaseFunction>,
+    pub transitions: Vec<StyleTransition>,
+}
+
+impl ComputedStyle {
+    /// applies the easing function to a transition ratio
+    pub fn ease(&self, ratio: f32) -> f32 {
+        self.easing
+            .and_then(|ease| EasingCurve::new(0., 1., ease).sample(ratio))
+            .unwrap_or(ratio)
+    }
+
+    /// the own timing of a property, if any
+    pub fn transition(&self, style: &StyleAttr) -> Option<&StyleTransition> {
+        let name = style.name();
+        self.transitions.iter().find(|t| t.property == name)
+    }
+
+    /// seconds until all properties entered a state
+    pub fn longest_transition(&self) -> f32 {
+        self.transitions
+            .iter()
+            .map(|t| t.duration)
+            .fold(self.delay.max(0.01), f32::max)
+    }
 }
 
 impl Default for ComputedStyle {
@@ -419,12 +1401,24 @@ impl Default for ComputedStyle {
             image_mode: None,
             shadow: None,
             image_region: None,
+            image_color: Color::WHITE,
+            flip_x: false,
+            flip_y: false,
+            z_index: None,
+            global_z_index: None,
+            visibility: None,
             outline: None,
             font: Handle::default(),
             font_size: 12.,
             font_color: Color::WHITE,
+            font_smoothing: FontSmoothing::default(),
+            text_justify: JustifyText::default(),
+            line_break: LineBreak::default(),
+            text_shadow: None,
             delay: 0.,
+            delay_out: None,
             easing: Some(EaseFunction::Linear),
+            transitions: vec![],
         }
     }
 }
@@ -438,6 +1432,12 @@ pub struct HtmlStyle {
     pub hover: Vec<StyleAttr>,
     pub pressed: Vec<StyleAttr>,
     pub active: Vec<StyleAttr>,
+    pub focus: Vec<StyleAttr>,
+    pub disabled: Vec<StyleAttr>,
+    pub states: HashMap<String, Vec<StyleAttr>>,
+    pub media: Vec<(MediaQuery, Vec<StyleAttr>)>,
+    pub enter: Vec<StyleAttr>,
+    pub exit: Vec<StyleAttr>,
 }
 
 impl From<Vec<StyleAttr>> for HtmlStyle {
@@ -453,37 +1453,20 @@ impl From<Vec<StyleAttr>> for HtmlStyle {
 impl HtmlStyle {
     pub fn add_style_attr(&mut self, attr: StyleAttr) {
         match attr {
-            StyleAttr::Hover(style) => {
-                let style = *style;
-                match self
-                    .hover
-                    .iter()
-                    .position(|s| std::mem::discriminant(s) == std::mem::discriminant(&style))
-                {
-                    Some(index) => self.hover.insert(index, style),
-                    None => self.hover.push(style),
-                }
-            }
-            StyleAttr::Pressed(style) => {
-                let style = *style;
-                match self
-                    .pressed
-                    .iter()
-                    .position(|s| std::mem::discriminant(s) == std::mem::discriminant(&style))
-                {
-                    Some(index) => self.pressed.insert(index, style),
-                    None => self.pressed.push(style),
-                }
+            StyleAttr::Hover(style) => replace_or_push(&mut self.hover, *style),
+            StyleAttr::Pressed(style) => replace_or_push(&mut self.pressed, *style),
+            StyleAttr::Active(style) => replace_or_push(&mut self.active, *style),
+            StyleAttr::Focus(style) => replace_or_push(&mut self.focus, *style),
+            StyleAttr::Disabled(style) => replace_or_push(&mut self.disabled, *style),
+            StyleAttr::Enter(style) => replace_or_push(&mut self.enter, *style),
+            StyleAttr::Exit(style) => replace_or_push(&mut self.exit, *style),
+            StyleAttr::State(state, style) => {
+                replace_or_push(self.states.entry(state).or_default(), *style)
             }
-            StyleAttr::Active(style) => {
-                let style = *style;
-                match self
-                    .active
-                    .iter()
-                    .position(|s| std::mem::discriminant(s) == std::mem::discriminant(&style))
-                {
-                    Some(index) => self.active.insert(index, style),
-                    None => self.active.push(style),
+            StyleAttr::Media(query, style) => {
+                match self.media.iter_mut().find(|(q, _)| *q == query) {
+                    Some((_, styles)) => replace_or_push(styles, *style),
+                    None => self.media.push((query, vec![*style])),
                 }
             }
             StyleAttr::Display(display) => self.computed.node.display = display,
@@ -542,11 +1525,23 @@ impl HtmlStyle {
             }
             StyleAttr::FontSize(f) => self.computed.font_size = f,
             StyleAttr::FontColor(color) => self.computed.font_color = color,
+            StyleAttr::FontSmoothing(smoothing) => self.computed.font_smoothing = smoothing,
+            StyleAttr::TextJustify(justify) => self.computed.text_justify = justify,
+            StyleAttr::LineBreak(linebreak) => self.computed.line_break = linebreak,
+            StyleAttr::TextShadow(shadow) => self.computed.text_shadow = Some(shadow),
             StyleAttr::Background(color) => self.computed.background = color,
             StyleAttr::Delay(f) => self.computed.delay = f,
+            StyleAttr::DelayOut(f) => self.computed.delay_out = Some(f),
+            StyleAttr::Transition(transitions) => self.computed.transitions = transitions,
             StyleAttr::Easing(ease) => self.computed.easing = Some(ease),
             StyleAttr::ImageScaleMode(mode) => self.computed.image_mode = Some(mode),
             StyleAttr::ImageRegion(rect) => self.computed.image_region = Some(rect),
+            StyleAttr::ImageColor(color) => self.computed.image_color = color,
+            StyleAttr::FlipX(flip) => self.computed.flip_x = flip,
+            StyleAttr::FlipY(flip) => self.computed.flip_y = flip,
+            StyleAttr::ZIndex(z_index) => self.computed.z_index = Some(z_index),
+            StyleAttr::GlobalZIndex(z_index) => self.computed.global_z_index = Some(z_index),
+            StyleAttr::Visibility(visibility) => self.computed.visibility = Some(visibility),
             StyleAttr::Outline(outline) => self.computed.outline = Some(outline),
 
             StyleAttr::ShadowSpread(spread_radius) => match self.computed.shadow.as_mut() {
@@ -592,6 +1587,127 @@ impl HtmlStyle {
     }
 }
 
+/// the ratio of a style, properties with their
+/// own `transition` follow the timer instead.
+fn transition_ratio(
+    computed: &ComputedStyle,
+    style: &StyleAttr,
+    ratio: f32,
+    timer: Option<&InteractionTimer>,
+) -> f32 {
+    timer
+        .and_then(|timer| {
+            let transition = computed.transition(style)?;
+            let fraction = timer.property_fraction(&transition.property)?;
+            transition
+                .easing
+                .or(computed.easing)
+                .and_then(|ease| EasingCurve::new(0., 1., ease).sample(fraction))
+                .or(Some(fraction))
+        })
+        .unwrap_or(ratio)
+}
+
+/// the value of a style at `ratio` starting at `from`,
+/// `None` keeps the start value.
+fn lerp_attr(from: &ComputedStyle, attr: &StyleAttr, ratio: f32) -> Option<StyleAttr> {
+    let node = &from.node;
+    let shadow = from.shadow.unwrap_or_default();
+    Some(match attr {
+        StyleAttr::Left(val) => StyleAttr::Left(lerp_val(&node.left, val, ratio)),
+        StyleAttr::Right(val) => StyleAttr::Right(lerp_val(&node.right, val, ratio)),
+        StyleAttr::Top(val) => StyleAttr::Top(lerp_val(&node.top, val, ratio)),
+        StyleAttr::Bottom(val) => StyleAttr::Bottom(lerp_val(&node.bottom, val, ratio)),
+        StyleAttr::Width(val) => StyleAttr::Width(lerp_val(&node.width, val, ratio)),
+        StyleAttr::Height(val) => StyleAttr::Height(lerp_val(&node.height, val, ratio)),
+        StyleAttr::MinWidth(val) => StyleAttr::MinWidth(lerp_val(&node.min_width, val, ratio)),
+        StyleAttr::MinHeight(val) => StyleAttr::MinHeight(lerp_val(&node.min_height, val, ratio)),
+        StyleAttr::MaxWidth(val) => StyleAttr::MaxWidth(lerp_val(&node.max_width, val, ratio)),
+        StyleAttr::MaxHeight(val) => StyleAttr::MaxHeight(lerp_val(&node.max_height, val, ratio)),
+        StyleAttr::FlexBasis(val) => StyleAttr::FlexBasis(lerp_val(&node.flex_basis, val, ratio)),
+        StyleAttr::RowGap(val) => StyleAttr::RowGap(lerp_val(&node.row_gap, val, ratio)),
+        StyleAttr::ColumnGap(val) => StyleAttr::ColumnGap(lerp_val(&node.column_gap, val, ratio)),
+        StyleAttr::FlexGrow(f) => StyleAttr::FlexGrow(node.flex_grow.lerp(*f, ratio)),
+        StyleAttr::FlexShrink(f) => StyleAttr::FlexShrink(node.flex_shrink.lerp(*f, ratio)),
+        StyleAttr::Margin(rect) => StyleAttr::Margin(lerp_rect(&node.margin, rect, ratio)),
+        StyleAttr::Padding(rect) => StyleAttr::Padding(lerp_rect(&node.padding, rect, ratio)),
+        StyleAttr::Border(rect) => StyleAttr::Border(lerp_rect(&node.border, rect, ratio)),
+        StyleAttr::BorderRadius(rect) => {
+            StyleAttr::BorderRadius(lerp_rect(&from.border_radius, rect, ratio))
+        }
+        StyleAttr::BorderColor(color) => {
+            StyleAttr::BorderColor(lerp_color(&from.border_color, color, ratio))
+        }
+        StyleAttr::Background(color) => {
+            StyleAttr::Background(lerp_color(&from.background, color, ratio))
+        }
+        StyleAttr::FontColor(color) => {
+            StyleAttr::FontColor(lerp_color(&from.font_color, color, ratio))
+        }
+        StyleAttr::ImageColor(color) => {
+            StyleAttr::ImageColor(lerp_color(&from.image_color, color, ratio))
+        }
+        StyleAttr::FontSize(size) => StyleAttr::FontSize(from.font_size.lerp(*size, ratio)),
+        StyleAttr::ShadowColor(color) => {
+            StyleAttr::ShadowColor(lerp_color(&shadow.color, color, ratio))
+        }
+        StyleAttr::ShadowOffset(x, y) => StyleAttr::ShadowOffset(
+            lerp_val(&shadow.x_offset, x, ratio),
+            lerp_val(&shadow.y_offset, y, ratio),
+        ),
+        StyleAttr::ShadowBlur(blur) => {
+            StyleAttr::ShadowBlur(lerp_val(&shadow.blur_radius, blur, ratio))
+        }
+        StyleAttr::ShadowSpread(spread) => {
+            StyleAttr::ShadowSpread(lerp_val(&shadow.spread_radius, spread, ratio))
+        }
+        _ if ratio > 0. => attr.clone(),
+        _ => return None,
+    })
+}
+
+/// a conditional style replaces an earlier one of the same kind
+fn replace_or_push(styles: &mut Vec<StyleAttr>, style: StyleAttr) {
+    match styles
+        .iter()
+        .position(|s| std::mem::discriminant(s) == std::mem::discriminant(&style))
+    {
+        Some(index) => styles[index] = style,
+        None => styles.push(style),
+    }
+}
+
+/// a `GlobalZIndex` starts a new stacking context, it
+/// only exists while a style declares one.
+fn set_global_z_index(
+    global_z_index: &mut Query<&mut GlobalZIndex>,
+    cmd: &mut Commands,
+    entity: Entity,
+    z_index: Option<i32>,
+) {
+    match (global_z_index.get_mut(entity), z_index) {
+        (Ok(mut current), Some(z_index)) => {
+            current.set_if_neq(GlobalZIndex(z_index));
+        }
+        (Ok(_), None) => {
+            cmd.entity(entity).remove::<GlobalZIndex>();
+        }
+        (Err(_), Some(z_index)) => {
+            cmd.entity(entity).try_insert(GlobalZIndex(z_index));
+        }
+        (Err(_), None) => (),
+    }
+}
+
+/// discrete values switch as soon as a state starts
+fn snap<T>(regular: T, target: T, ratio: f32) -> T {
+    if ratio > 0. {
+        target
+    } else {
+        regular
+    }
+}
+
 fn lerp_color(start: &Color, end: &Color, ratio: f32) -> Color {
     let lin = start
         .to_linear()
@@ -619,3 +1735,217 @@ fn lerp_val(start: &Val, end: &Val, ratio: f32) -> Val {
         _ => *start,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{ecs::system::RunSystemOnce, time::TimeUpdateStrategy};
+
+    #[test]
+    fn test_despawn_animated_twice() {
+        let mut world = World::new();
+        let parent = world.spawn_empty().id();
+        let child = world.spawn_empty().set_parent(parent).id();
+
+        let mut cmd = world.commands();
+        cmd.entity(parent).despawn_animated();
+        cmd.entity(parent).despawn_animated();
+        world.flush();
+
+        assert!(world.get_entity(parent).is_err());
+        assert!(world.get_entity(child).is_err());
+    }
+
+    #[test]
+    fn test_owned_node_fields() {
+        let owned = UiStyleOwned::new(["width", "flex_grow"]);
+        let current = Node {
+            width: Val::Px(10.),
+            flex_grow: 2.,
+            ..default()
+        };
+
+        let mut next = Node {
+            width: Val::Px(50.),
+            height: Val::Px(50.),
+            ..default()
+        };
+
+        owned.keep(&current, &mut next);
+        assert_eq!(next.width, Val::Px(10.));
+        assert_eq!(next.flex_grow, 2.);
+        assert_eq!(next.height, Val::Px(50.));
+    }
+
+    #[test]
+    fn test_timer_settles() {
+        let style = ComputedStyle::default();
+        let mut timer = InteractionTimer::new(Duration::from_secs_f32(0.1));
+        assert!(timer.is_settled(false));
+        assert!(!timer.is_settled(true));
+
+        timer.step(Duration::from_secs(1), true, &style);
+        assert!(timer.is_settled(true));
+        assert!(!timer.is_settled(false));
+    }
+
+    #[test]
+    fn test_interrupted_tween() {
+        let from = HtmlStyle::from(vec![
+            StyleAttr::Delay(1.),
+            StyleAttr::Width(Val::Px(0.)),
+            StyleAttr::Height(Val::Px(0.)),
+        ])
+        .computed;
+        let target = HtmlStyle::from(vec![
+            StyleAttr::Delay(1.),
+            StyleAttr::Width(Val::Px(100.)),
+            StyleAttr::Height(Val::Px(10.)),
+        ])
+        .computed;
+
+        let mut tween = StyleTween::default();
+        assert!(tween.current(&target).is_none());
+
+        tween.start(from, vec![StyleAttr::Width(Val::Px(100.))]);
+        tween.timer.forward(Duration::from_millis(500));
+
+        let current = tween.current(&target).unwrap();
+        assert_eq!(current.node.width, Val::Px(50.));
+
+        // the height restarts from the current width
+        tween.start(current, vec![StyleAttr::Height(Val::Px(10.))]);
+        assert_eq!(tween.styles.len(), 2);
+
+        let current = tween.current(&target).unwrap();
+        assert_eq!(current.node.width, Val::Px(50.));
+        assert_eq!(current.node.height, Val::Px(0.));
+    }
+
+    #[test]
+    fn test_enter_eases_forward() {
+        let mut app = crate::test_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        crate::spawn_template(
+            &mut app,
+            r#"<template><node><node width="100px" enter:width="0px" ease="cubic_out" delay="1"/></node></template>"#,
+        );
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let (node, timer) = world.query::<(&Node, &EnterTimer)>().single(world);
+        let fraction = timer.fraction();
+        assert!(fraction > 0. && fraction < 1.);
+
+        // eases out of the enter style, ahead of linear
+        let eased = EasingCurve::new(0., 1., EaseFunction::CubicOut)
+            .sample(fraction)
+            .unwrap();
+        let Val::Px(width) = node.width else {
+            panic!("width is not px");
+        };
+        assert!((width - 100. * eased).abs() < 0.01);
+        assert!(width > 100. * fraction);
+    }
+
+    #[test]
+    fn test_conditional_global_z_index() {
+        let mut app = crate::test_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        crate::spawn_template(
+            &mut app,
+            r#"<template><property name="z">4</property><node><node id="expr" global_z_index="{z}"/><node id="state" state(open):global_z_index="3"/></node></template>"#,
+        );
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let find = |app: &mut App, id: &str| {
+            let world = app.world_mut();
+            world
+                .query::<(Entity, &crate::prelude::UiId)>()
+                .iter(world)
+                .find_map(|(entity, ui_id)| (**ui_id == id).then_some(entity))
+                .unwrap()
+        };
+
+        let expr = find(&mut app, "expr");
+        let state = find(&mut app, "state");
+        assert_eq!(app.world().get::<GlobalZIndex>(expr).unwrap().0, 4);
+        assert!(app.world().get::<GlobalZIndex>(state).is_none());
+
+        app.world_mut()
+            .get_mut::<UiStates>(state)
+            .unwrap()
+            .insert("open");
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.world().get::<GlobalZIndex>(state).unwrap().0, 3);
+
+        app.world_mut()
+            .get_mut::<UiStates>(state)
+            .unwrap()
+            .remove("open");
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(app.world().get::<GlobalZIndex>(state).is_none());
+    }
+
+    #[test]
+    fn test_viewport_camera_of_root() {
+        let mut app = crate::test_app();
+        let world = app.world_mut();
+        let camera = world.spawn_empty().id();
+        let root = world.spawn(TargetCamera(camera)).id();
+        let child = world.spawn_empty().set_parent(root).id();
+        let leaf = world.spawn_empty().set_parent(child).id();
+
+        let found = world
+            .run_system_once(move |viewport: UiViewport| viewport.camera(leaf))
+            .unwrap();
+        assert_eq!(found, Some(camera));
+    }
+
+    #[test]
+    fn test_keep_undeclared_layering() {
+        let mut app = crate::test_app();
+        crate::spawn_template(
+            &mut app,
+            r#"<template><node><node z_index="2"/><node/></node></template>"#,
+        );
+        app.update();
+        app.update();
+
+        let world = app.world_mut();
+        let mut nodes = world
+            .query_filtered::<Entity, (With<HtmlStyle>, Without<Children>)>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|entity| world.get::<ZIndex>(*entity).unwrap().0);
+        let [plain, declared] = nodes[..] else {
+            panic!("expected two leaf nodes");
+        };
+
+        world
+            .entity_mut(plain)
+            .insert((ZIndex(5), Visibility::Hidden));
+        for entity in [plain, declared] {
+            world.get_mut::<HtmlStyle>(entity).unwrap().set_changed();
+        }
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<ZIndex>(declared).unwrap().0, 2);
+        assert_eq!(world.get::<ZIndex>(plain).unwrap().0, 5);
+        assert_eq!(world.get::<Visibility>(plain), Some(&Visibility::Hidden));
+    }
+}
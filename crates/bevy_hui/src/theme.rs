@@ -0,0 +1,162 @@
+use crate::{
+    build::{HtmlNode, TemplateProperties, TemplateScope},
+    compile::CompileContextEvent,
+};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+pub struct ThemePlugin;
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HuiTheme>()
+            .register_type::<HuiTheme>()
+            .add_systems(Update, update_theme);
+    }
+}
+
+/// Global variables, available in every template
+/// as `{theme.primary}`. Local properties win.
+///
+/// Changing the resource recompiles all live templates.
+///
+/// `
+/// app.insert_resource(HuiTheme::default().with("primary", "#333"));
+/// `
+#[derive(Resource, Debug, Clone, Default, Reflect, Deref, DerefMut)]
+#[reflect(Resource)]
+pub struct HuiTheme(HashMap<String, String>);
+
+impl HuiTheme {
+    pub fn with(mut self, key: &str, value: &str) -> Self {
+        self.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// the theme values as template property keys
+    pub(crate) fn properties(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        self.iter()
+            .map(|(key, value)| (format!("theme.{key}"), value.clone()))
+    }
+
+    /// replaces the values of the previous theme, values
+    /// defined by the template itself are kept.
+    pub(crate) fn apply(&self, owned: &mut ThemeKeys, props: &mut TemplateProperties) {
+        for key in owned.drain() {
+            props.remove(&key);
+        }
+
+        for (key, value) in self.properties() {
+            if !props.contains_key(&key) {
+                owned.insert(key.clone());
+                props.insert(key, value);
+            }
+        }
+    }
+}
+
+/// the template property keys, that came from the
+/// theme and are replaced, when the theme changes.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub(crate) struct ThemeKeys(HashSet<String>);
+
+fn update_theme(
+    mut cmd: Commands,
+    theme: Res<HuiTheme>,
+    mut properties: Query<(&mut TemplateProperties, &mut ThemeKeys)>,
+    roots: Query<Entity, (With<HtmlNode>, Without<TemplateScope>)>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+
+    properties.iter_mut().for_each(|(mut props, mut owned)| {
+        theme.apply(&mut owned, &mut props);
+    });
+
+    roots.iter().for_each(|root| {
+        cmd.trigger_targets(CompileContextEvent, root);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_swap_theme() {
+        let dark = HuiTheme::default()
+            .with("primary", "#000")
+            .with("gap", "5px");
+        let light = HuiTheme::default().with("primary", "#FFF");
+
+        let mut owned = ThemeKeys::default();
+        let mut props = TemplateProperties::default().with("theme.gap", "10px");
+        dark.apply(&mut owned, &mut props);
+        assert_eq!(props.get("theme.primary").unwrap(), "#000");
+        assert_eq!(props.get("theme.gap").unwrap(), "10px");
+
+        light.apply(&mut owned, &mut props);
+        assert_eq!(props.get("theme.primary").unwrap(), "#FFF");
+        assert_eq!(props.get("theme.gap").unwrap(), "10px");
+    }
+
+    #[test]
+    fn test_keep_local_value_equal_to_theme() {
+        let dark = HuiTheme::default().with("gap", "5px");
+        let light = HuiTheme::default().with("gap", "8px");
+
+        let mut owned = ThemeKeys::default();
+        let mut props = TemplateProperties::default().with("theme.gap", "5px");
+        dark.apply(&mut owned, &mut props);
+        light.apply(&mut owned, &mut props);
+        assert_eq!(props.get("theme.gap").unwrap(), "5px");
+
+        HuiTheme::default().apply(&mut owned, &mut props);
+        assert_eq!(props.get("theme.gap").unwrap(), "5px");
+    }
+
+    #[test]
+    fn test_theme_change_recompiles() {
+        let mut app = crate::test_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(1)));
+        app.insert_resource(
+            HuiTheme::default()
+                .with("size", "10px")
+                .with("name", "dark"),
+        );
+        crate::spawn_template(
+            &mut app,
+            r#"<template><node><node width="{theme.size}"/><text>{theme.name}</text></node></template>"#,
+        );
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let read = |app: &mut App| {
+            let world = app.world_mut();
+            let width = world
+                .query_filtered::<&Node, (With<TemplateScope>, Without<Text>)>()
+                .single(world)
+                .width;
+            let text = world.query::<&Text>().single(world).0.clone();
+            (width, text)
+        };
+
+        assert_eq!(read(&mut app), (Val::Px(10.), "dark".to_string()));
+
+        app.insert_resource(
+            HuiTheme::default()
+                .with("size", "20px")
+                .with("name", "light"),
+        );
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(read(&mut app), (Val::Px(20.), "light".to_string()));
+    }
+}
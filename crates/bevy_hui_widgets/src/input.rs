@@ -26,6 +26,8 @@ use bevy_hui::prelude::*;
 ///     in the template. This will display the current value
 /// -   the input consumes any key events, when `UiActive` is attached.
 ///     you can use conditional styles with `active:border_color="..`
+/// -   add an optional `bind="{name}"` to sync the value with a property
+///     of the owning template.
 ///
 /// ## Minimal template example:
 ///
@@ -56,7 +58,9 @@ impl Plugin for HuiInputWidgetPlugin {
             (
                 unfocus,
                 focus.after(unfocus),
+                read_input_binding.before(write_input),
                 write_input,
+                write_input_binding.after(write_input),
                 sync_text_preview,
             ),
         );
@@ -116,10 +120,13 @@ fn setup(mut html_funcs: HtmlFunctions) {
                 .flatten()
                 .unwrap_or_default();
 
-            cmd.entity(entity).insert(TextInput {
-                value: default(),
-                filter,
-            });
+            cmd.entity(entity).insert((
+                TextInput {
+                    value: default(),
+                    filter,
+                },
+                UiTextInput,
+            ));
         },
     );
 }
@@ -208,3 +215,29 @@ fn sync_text_preview(
         cmd.trigger_targets(UiChangedEvent, entity);
     }
 }
+
+/// updates the value, when the bound property
+/// was changed from outside.
+fn read_input_binding(
+    bindings: HtmlBindings,
+    mut inputs: Query<(Entity, &mut TextInput), With<UiBind>>,
+) {
+    for (entity, mut input) in inputs.iter_mut() {
+        let Some(value) = bindings.read(entity) else {
+            continue;
+        };
+
+        if input.value != value {
+            input.value = value.to_string();
+        }
+    }
+}
+
+fn write_input_binding(
+    mut bindings: HtmlBindings,
+    inputs: Query<(Entity, &TextInput), (Changed<TextInput>, With<UiBind>)>,
+) {
+    for (entity, input) in inputs.iter() {
+        bindings.write(entity, &input.value);
+    }
+}
@@ -6,6 +6,9 @@ use bevy_hui::prelude::*;
 /// A select is a button with 2 children. The current
 /// selected node and a hidden node, holding the options.
 ///
+/// Add an optional `bind="{key}"` to sync the `tag:value`
+/// of the selected option with a property of the owning template.
+///
 pub struct HuiSelectWidgetPlugin;
 impl Plugin for HuiSelectWidgetPlugin {
     fn build(&self, app: &mut App) {
@@ -17,10 +20,12 @@ impl Plugin for HuiSelectWidgetPlugin {
         app.add_systems(
             Update,
             (
+                read_select_binding,
                 open_list,
                 selection,
                 update_selection.run_if(on_event::<SelectionChangedEvent>),
-            ),
+            )
+                .chain(),
         );
     }
 }
@@ -113,10 +118,25 @@ fn update_selection(
     mut cmd: Commands,
     mut events: EventReader<SelectionChangedEvent>,
     mut texts: Query<&mut Text>,
+    mut selects: Query<&mut SelectInput>,
+    mut bindings: HtmlBindings,
     children: Query<&Children>,
     tags: Query<&Tags>,
 ) {
     for event in events.read() {
+        _ = selects.get_mut(event.select).map(|mut select| {
+            select.value = Some(event.option);
+        });
+
+        let option_value = tags
+            .get(event.option)
+            .ok()
+            .and_then(|tags| tags.get("value"))
+            .cloned()
+            .unwrap_or_default();
+
+        bindings.write(event.select, option_value);
+
         let Some(mut text) = children
             .get(event.select)
             .ok()
@@ -141,3 +161,40 @@ fn update_selection(
         cmd.trigger_targets(UiChangedEvent, event.select);
     }
 }
+
+/// selects the matching option, when the bound
+/// property was changed from outside.
+fn read_select_binding(
+    mut events: EventWriter<SelectionChangedEvent>,
+    bindings: HtmlBindings,
+    selects: Query<(Entity, &SelectInput), With<UiBind>>,
+    options: Query<(Entity, &SelectOption, &Tags)>,
+    tags: Query<&Tags>,
+) {
+    for (entity, select) in selects.iter() {
+        let Some(value) = bindings.read(entity) else {
+            continue;
+        };
+
+        let current = select
+            .value
+            .and_then(|option| tags.get(option).ok())
+            .and_then(|tags| tags.get("value"));
+
+        if current.map(|c| c.as_str()) == Some(value) {
+            continue;
+        }
+
+        let Some(option) = options.iter().find_map(|(option, select_option, tags)| {
+            (select_option.select == entity && tags.get("value").map(|v| v.as_str()) == Some(value))
+                .then_some(option)
+        }) else {
+            continue;
+        };
+
+        events.send(SelectionChangedEvent {
+            select: entity,
+            option,
+        });
+    }
+}
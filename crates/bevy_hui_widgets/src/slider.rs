@@ -15,6 +15,8 @@ use bevy_hui::prelude::*;
 /// -   create a template/custom component.
 /// -   attach the init_slider function to the root node
 /// -   add an optional `tag:axis="x/y"`
+/// -   add an optional `bind="{volume}"` to sync the value (0-1)
+///     with a property of the owning template.
 ///
 /// ## Minimal template example:
 ///
@@ -47,6 +49,7 @@ impl Plugin for HuiSliderWidgetPlugin {
         app.add_systems(
             Update,
             (
+                read_slider_binding.before(update_drag),
                 update_drag,
                 update_slider_value.run_if(on_event::<SliderChangedEvent>),
             ),
@@ -172,9 +175,7 @@ fn update_drag(
                             _ => 0.,
                         };
 
-                        let max_pos = slider_computed.unrounded_size().x
-                            * slider_computed.inverse_scale_factor()
-                            - nob_computed.unrounded_size().x * nob_computed.inverse_scale_factor();
+                        let max_pos = nob_range(&slider.axis, slider_computed, nob_computed);
 
                         let next_pos = (current_pos
                             + event.delta.x / slider_computed.inverse_scale_factor())
@@ -194,9 +195,7 @@ fn update_drag(
                             _ => 0.,
                         };
 
-                        let max_pos = slider_computed.unrounded_size().y
-                            * slider_computed.inverse_scale_factor()
-                            - nob_computed.unrounded_size().y * nob_computed.inverse_scale_factor();
+                        let max_pos = nob_range(&slider.axis, slider_computed, nob_computed);
 
                         let next_pos = (current_pos
                             - event.delta.y / slider_computed.inverse_scale_factor())
@@ -219,11 +218,69 @@ fn update_slider_value(
     mut cmd: Commands,
     mut events: EventReader<SliderChangedEvent>,
     mut sliders: Query<(Entity, &mut Slider)>,
+    mut bindings: HtmlBindings,
 ) {
     for event in events.read() {
         _ = sliders.get_mut(event.slider).map(|(entity, mut slider)| {
             slider.value = event.value;
+            bindings.write(entity, slider.value);
             cmd.trigger_targets(UiChangedEvent, entity);
         });
     }
 }
+
+/// moves the nob, when the bound property
+/// was changed from outside.
+fn read_slider_binding(
+    bindings: HtmlBindings,
+    mut sliders: Query<(Entity, &mut Slider), With<UiBind>>,
+    mut nobs: Query<(&SliderNob, &mut HtmlStyle, &ComputedNode)>,
+    computed_nodes: Query<&ComputedNode, With<Slider>>,
+) {
+    for (entity, mut slider) in sliders.iter_mut() {
+        let Some(value) = bindings
+            .read(entity)
+            .and_then(|value| value.trim().parse::<f32>().ok())
+            .map(|value| value.clamp(0., 1.))
+        else {
+            continue;
+        };
+
+        if (value - slider.value).abs() < f32::EPSILON {
+            continue;
+        }
+
+        let Some((_, mut style, nob_computed)) =
+            nobs.iter_mut().find(|(nob, _, _)| nob.slider == entity)
+        else {
+            continue;
+        };
+
+        let Ok(slider_computed) = computed_nodes.get(entity) else {
+            continue;
+        };
+
+        // wait for the layout
+        let max_pos = nob_range(&slider.axis, slider_computed, nob_computed);
+        if max_pos <= 0. {
+            continue;
+        }
+
+        match slider.axis {
+            SliderAxis::Horizontal => style.computed.node.left = Val::Px(value * max_pos),
+            SliderAxis::Vertical => style.computed.node.bottom = Val::Px(value * max_pos),
+        }
+
+        slider.value = value;
+    }
+}
+
+/// the distance the nob can travel in logical pixels
+fn nob_range(axis: &SliderAxis, slider: &ComputedNode, nob: &ComputedNode) -> f32 {
+    let slider_size = slider.unrounded_size() * slider.inverse_scale_factor();
+    let nob_size = nob.unrounded_size() * nob.inverse_scale_factor();
+    match axis {
+        SliderAxis::Horizontal => slider_size.x - nob_size.x,
+        SliderAxis::Vertical => slider_size.y - nob_size.y,
+    }
+}
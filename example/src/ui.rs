@@ -44,7 +44,7 @@ fn setup(
     html_funcs.register("greet", greet);
     html_funcs.register("inventory", init_inventory);
     html_funcs.register("scrollable", init_scrollable);
-    html_funcs.register("play_beep", play_beep);
+    html_funcs.register_with_args("play_beep", play_beep);
     html_funcs.register("collapse", |In(entity), mut cmd: Commands| {
         cmd.entity(entity).insert(Collapse(true));
     });
@@ -197,20 +197,15 @@ fn init_inventory(In(entity): In<Entity>, mut cmd: Commands, server: Res<AssetSe
 }
 
 fn play_beep(
-    In(entity): In<Entity>,
-    tags: Query<&Tags>,
+    In((_, args)): In<(Entity, HuiArgs)>,
     mut cmd: Commands,
     server: Res<AssetServer>,
 ) {
-    let Some(path) = tags
-        .get(entity)
-        .ok()
-        .and_then(|t| t.get("source").map(|s| s.to_string()))
-    else {
+    let Some(path) = args.str(0) else {
         return;
     };
 
-    let beep: Handle<AudioSource> = server.load(&path);
+    let beep: Handle<AudioSource> = server.load(path);
     cmd.spawn((
         AudioPlayer(beep),
         PlaybackSettings::ONCE,